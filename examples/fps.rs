@@ -0,0 +1,31 @@
+use std::{env, fs, time::Instant};
+use toaster_nes::rom::rom_parse;
+use toaster_nes::*;
+
+const DEFAULT_FRAMES: u32 = 3000;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let rom_data = fs::read(&args[1]).unwrap();
+    let rom = rom_parse(&rom_data).unwrap();
+    let num_frames = args
+        .get(2)
+        .map_or(DEFAULT_FRAMES, |frames| frames.parse().unwrap());
+
+    let mut nes = Nes::init(&rom).unwrap();
+    let mut frame = [0; FRAME_SIZE_BYTES];
+
+    let time = Instant::now();
+    for _ in 0..num_frames {
+        nes.frame(&mut frame);
+    }
+    let elapsed = time.elapsed().as_secs_f64();
+
+    println!(
+        "{} frames in {:.3}s: {:.1} FPS",
+        num_frames,
+        elapsed,
+        num_frames as f64 / elapsed
+    );
+}
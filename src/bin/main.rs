@@ -1,20 +1,46 @@
+#[cfg(feature = "window")]
 #[path = "window/window.rs"]
 pub mod window;
 
 use lazy_static::lazy_static;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
 use std::{
-    env, fs, thread,
-    time::{Duration, Instant},
+    env, fs, process, thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use toaster_nes::capture::Y4mWriter;
+use toaster_nes::hash::crc32;
+use toaster_nes::movie::{fm2_parse, MovieFrame};
+use toaster_nes::nsf::{is_nsf, nsf_get_info, nsf_parse, Nsf};
+use toaster_nes::ntsc::{NtscFilter, NtscFilterParams, NTSC_FRAME_SIZE_BYTES, NTSC_WIDTH};
+use toaster_nes::palette::{ntsc_palette, pal_parse, NtscParams, Palette};
+use toaster_nes::patch::patch_apply;
+use toaster_nes::png::png_encode;
+use toaster_nes::rom::game_db::{db_apply, db_load};
+use toaster_nes::rom::unif::{is_unif, unif_parse};
 use toaster_nes::rom::{rom_get_info, rom_parse};
+use toaster_nes::test_rom::{test_rom_run, TestRomResult};
 use toaster_nes::*;
+#[cfg(feature = "window")]
 use window::*;
 
 const WINDOW_TITLE: &str = "ToasterNES";
 const WINDOW_SCALE: u32 = 3;
-const FRAME_TIME_US: u128 = 16666;
+#[cfg(feature = "window")]
+const KEY_NEXT_TRACK: Key = Key::Right;
+#[cfg(feature = "window")]
+const KEY_PREV_TRACK: Key = Key::Left;
+#[cfg(feature = "window")]
+const KEY_SCREENSHOT: Key = Key::F12;
+const PATCH_EXTENSIONS: [&str; 3] = ["ips", "ups", "bps"];
+/// How long `--test-rom` waits for a result without `--frames`, one minute
+/// at 60 FPS.
+const TEST_ROM_MAX_FRAMES: usize = 3600;
 
+#[cfg(feature = "window")]
 lazy_static! {
     static ref KEY_BINDS: HashMap<Key, Button> = [
         (Key::W, Button::Up),
@@ -32,23 +58,199 @@ lazy_static! {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().partition(|arg| arg.starts_with("--"));
+    let region = flags.iter().find_map(|flag| match flag.as_str() {
+        "--ntsc" => Some(Region::Ntsc),
+        "--pal" => Some(Region::Pal),
+        "--dendy" => Some(Region::Dendy),
+        _ => None,
+    });
+    let palette = flags.iter().find_map(|flag| {
+        if flag == "--ntsc-palette" {
+            Some(ntsc_palette(&NtscParams::default()))
+        } else {
+            let path = flag.strip_prefix("--palette=")?;
+            let palette = fs::read(path).map_err(|err| err.to_string());
+            Some(
+                palette
+                    .and_then(|data| pal_parse(&data))
+                    .unwrap_or_else(|err| {
+                        eprintln!("Error loading palette {}: {}", path, err);
+                        process::exit(1);
+                    }),
+            )
+        }
+    });
+
+    if let Some(path) = flags
+        .iter()
+        .find_map(|flag| flag.strip_prefix("--game-db="))
+    {
+        let db = fs::read_to_string(path).map_err(|err| err.to_string());
+        db.and_then(|text| db_load(&text)).unwrap_or_else(|err| {
+            eprintln!("Error loading game database {}: {}", path, err);
+            process::exit(1);
+        });
+    }
+
+    let mut ntsc_filter = flags
+        .iter()
+        .any(|flag| flag == "--ntsc-filter")
+        .then(|| NtscFilter::init(NtscFilterParams::default()));
+
+    // Headless runs keep stdout for the frame hash, so scripts can capture it.
+    let headless = Headless::from_flags(&flags);
+    let info = |msg: &str| match headless {
+        Some(_) => eprintln!("{}", msg),
+        None => println!("{}", msg),
+    };
+
+    #[cfg(not(feature = "window"))]
+    if headless.is_none() {
+        eprintln!("Built without the window feature, so only headless runs are supported");
+        process::exit(1);
+    }
+
+    let mut rom_data = fs::read(&args[1]).unwrap();
+
+    if let Some(patch_path) = find_patch(&args) {
+        let patch = fs::read(&patch_path).unwrap();
+        rom_data = patch_apply(&rom_data, &patch).unwrap_or_else(|err| {
+            eprintln!("Error applying patch {}: {}", patch_path, err);
+            process::exit(1);
+        });
+        info(&format!("Applied patch {}", patch_path));
+    }
+
+    let nsf = is_nsf(&rom_data).then(|| {
+        nsf_parse(&rom_data).unwrap_or_else(|err| {
+            eprintln!("Error loading {}: {}", args[1], err);
+            process::exit(1);
+        })
+    });
+    let mut song = nsf.as_ref().map_or(0, |nsf| nsf.start_song);
 
-    let rom_data = fs::read(&args[1]).unwrap();
-    let rom = rom_parse(&rom_data).unwrap();
+    let mut nes = match &nsf {
+        Some(nsf) => {
+            info(&nsf_get_info(nsf));
+            eprintln!("Audio is not emulated yet, so the tune will be silent.");
+            Nes::init_nsf(nsf, song, region.unwrap_or(nsf.region()))
+        }
+        None => {
+            let rom = if is_unif(&rom_data) {
+                unif_parse(&rom_data)
+            } else {
+                rom_parse(&rom_data)
+            };
 
-    println!("{}", rom_get_info(&rom));
+            let nes = rom.and_then(|mut rom| {
+                if let Some(report) = db_apply(&mut rom) {
+                    info(&report);
+                }
+                info(&rom_get_info(&rom));
+                match region {
+                    Some(region) => Nes::init_with_region(&rom, region),
+                    None => Nes::init(&rom),
+                }
+            });
+
+            nes.unwrap_or_else(|err| {
+                eprintln!("Error loading {}: {}", args[1], err);
+                process::exit(1);
+            })
+        }
+    };
+
+    if let Some(palette) = &palette {
+        nes.set_palette(palette);
+    }
+
+    let width = match ntsc_filter {
+        Some(_) => NTSC_WIDTH as u32,
+        None => DISPLAY_WIDTH,
+    };
+    let mut recorder = Recorder::from_flags(&flags, nes.region(), width);
+
+    if let Some(headless) = headless {
+        let passed = headless.run(&mut nes, ntsc_filter.as_mut(), &mut recorder);
+        recorder.finish();
+        if !passed {
+            process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "window")]
+    run_window(
+        &args[1],
+        nes,
+        nsf.as_ref(),
+        song,
+        palette.as_ref(),
+        ntsc_filter,
+        &mut recorder,
+    );
+
+    recorder.finish();
+}
 
-    let mut nes = Nes::init(&rom);
+/// Runs the NES in a window until it's closed, playing with the keyboard.
+#[cfg(feature = "window")]
+fn run_window(
+    rom_path: &str,
+    mut nes: Nes,
+    nsf: Option<&Nsf>,
+    mut song: u8,
+    palette: Option<&Palette>,
+    mut ntsc_filter: Option<NtscFilter>,
+    recorder: &mut Recorder,
+) {
+    // Filtered frames are drawn with each row doubled, to keep the aspect
+    // ratio close to the unfiltered picture.
+    let mut window = match ntsc_filter {
+        Some(_) => Window::init(
+            WINDOW_TITLE,
+            NTSC_WIDTH as u32,
+            DISPLAY_HEIGHT * 2,
+            WINDOW_SCALE / 2,
+        ),
+        None => Window::init(WINDOW_TITLE, DISPLAY_WIDTH, DISPLAY_HEIGHT, WINDOW_SCALE),
+    };
 
-    let mut window = Window::init(WINDOW_TITLE, DISPLAY_WIDTH, DISPLAY_HEIGHT, WINDOW_SCALE);
+    if let Some(nsf) = nsf {
+        window.set_title(&nsf_title(nsf, song));
+    }
 
     let mut frame = [0; FRAME_SIZE_BYTES];
+    let mut frame_indices = [0; FRAME_SIZE_PIXELS];
+    let mut ntsc_frame = vec![0; NTSC_FRAME_SIZE_BYTES];
+    let mut ntsc_frame_doubled = vec![0; NTSC_FRAME_SIZE_BYTES * 2];
 
     while !window.closed() {
         let time = Instant::now();
 
-        nes.frame(&mut frame);
+        match &mut ntsc_filter {
+            Some(filter) => {
+                nes.frame_indices(&mut frame_indices);
+                filter.filter(&frame_indices, &mut ntsc_frame);
+
+                let row_size = NTSC_WIDTH * 3;
+                for (row, doubled) in ntsc_frame
+                    .chunks_exact(row_size)
+                    .zip(ntsc_frame_doubled.chunks_exact_mut(row_size * 2))
+                {
+                    doubled[..row_size].copy_from_slice(row);
+                    doubled[row_size..].copy_from_slice(row);
+                }
+            }
+            None => nes.frame(&mut frame),
+        }
+
+        match ntsc_filter {
+            Some(_) => recorder.frame(&ntsc_frame),
+            None => recorder.frame(&frame),
+        }
 
         window.poll_events();
 
@@ -56,13 +258,299 @@ fn main() {
             if let Some(&button) = KEY_BINDS.get(&key) {
                 nes.set_button_state(button, pressed)
             }
+
+            if key == KEY_SCREENSHOT && pressed {
+                let path = screenshot_path(rom_path);
+                let png = match ntsc_filter {
+                    Some(_) => png_encode(&ntsc_frame, NTSC_WIDTH as u32, DISPLAY_HEIGHT),
+                    None => png_encode(&frame, DISPLAY_WIDTH, DISPLAY_HEIGHT),
+                };
+
+                match fs::write(&path, png) {
+                    Ok(()) => println!("Saved screenshot {}", path.display()),
+                    Err(err) => eprintln!("Error saving screenshot {}: {}", path.display(), err),
+                }
+            }
+
+            if let (Some(nsf), true) = (nsf, pressed) {
+                let num_songs = nsf.num_songs;
+                let new_song = match key {
+                    KEY_NEXT_TRACK => (song + 1) % num_songs,
+                    KEY_PREV_TRACK => song.checked_sub(1).unwrap_or(num_songs - 1),
+                    _ => song,
+                };
+
+                if new_song != song {
+                    song = new_song;
+                    nes = Nes::init_nsf(nsf, song, nes.region());
+                    if let Some(palette) = palette {
+                        nes.set_palette(palette);
+                    }
+                    window.set_title(&nsf_title(nsf, song));
+                }
+            }
         }
 
-        let delay = (FRAME_TIME_US - time.elapsed().as_micros()) as i128;
+        let delay = nes.region().frame_time_us() as i128 - time.elapsed().as_micros() as i128;
         if delay > 0 {
             thread::sleep(Duration::from_micros(delay as u64));
         }
 
-        window.render(&frame);
+        if ntsc_filter.is_some() {
+            window.render(&ntsc_frame_doubled);
+        } else {
+            window.render(&frame);
+        }
     }
 }
+
+/// Options for running without a window, e.g. for regression tests on
+/// machines with no display.
+struct Headless {
+    frames: usize,
+    movie: Vec<MovieFrame>,
+    screenshot: Option<String>,
+    hash: bool,
+    test_rom: bool,
+}
+
+impl Headless {
+    /// Headless mode is used if any of `--frames=N`, `--input=<movie.fm2>`,
+    /// `--screenshot=<file.png>`, `--hash` or `--test-rom` are given. Without
+    /// `--frames`, the movie is played to its end.
+    fn from_flags(flags: &[String]) -> Option<Self> {
+        let flag_val = |name: &str| flags.iter().find_map(|flag| flag.strip_prefix(name));
+
+        let frames = flag_val("--frames=").map(|frames| {
+            frames.parse().unwrap_or_else(|_| {
+                eprintln!("Bad frame count: {}", frames);
+                process::exit(1);
+            })
+        });
+        let movie = flag_val("--input=").map(|path| {
+            let text = fs::read_to_string(path).map_err(|err| err.to_string());
+            text.and_then(|text| fm2_parse(&text))
+                .unwrap_or_else(|err| {
+                    eprintln!("Error loading movie {}: {}", path, err);
+                    process::exit(1);
+                })
+        });
+        let screenshot = flag_val("--screenshot=").map(String::from);
+        let hash = flags.iter().any(|flag| flag == "--hash");
+        let test_rom = flags.iter().any(|flag| flag == "--test-rom");
+
+        if frames.is_none() && movie.is_none() && screenshot.is_none() && !hash && !test_rom {
+            return None;
+        }
+
+        if test_rom && (movie.is_some() || screenshot.is_some() || hash) {
+            eprintln!("--test-rom can't be used with --input, --screenshot or --hash");
+            process::exit(1);
+        }
+
+        let movie = movie.unwrap_or_default();
+        let frames = match frames {
+            Some(frames) => frames,
+            None if test_rom => TEST_ROM_MAX_FRAMES,
+            None => movie.len(),
+        };
+        if frames == 0 {
+            eprintln!("Headless mode needs --frames=N or a movie to play");
+            process::exit(1);
+        }
+
+        Some(Self {
+            frames,
+            movie,
+            screenshot,
+            hash,
+            test_rom,
+        })
+    }
+
+    /// Runs the frames, playing back the movie's inputs, then saves and
+    /// hashes the last frame, after the NTSC filter if it's enabled. With
+    /// `--test-rom`, runs until the ROM reports a result at $6000 instead.
+    /// Returns whether the run passed.
+    fn run(
+        &self,
+        nes: &mut Nes,
+        mut ntsc_filter: Option<&mut NtscFilter>,
+        recorder: &mut Recorder,
+    ) -> bool {
+        if self.test_rom {
+            return match test_rom_run(nes, self.frames as u32) {
+                TestRomResult::Passed(msg) => {
+                    println!("{}", msg);
+                    true
+                }
+                TestRomResult::Failed(code, msg) => {
+                    println!("{}", msg);
+                    eprintln!("Failed with code {}", code);
+                    false
+                }
+                TestRomResult::TimedOut => {
+                    eprintln!("No result after {} frames", self.frames);
+                    false
+                }
+            };
+        }
+
+        let mut frame = [0; FRAME_SIZE_BYTES];
+        let mut frame_indices = [0; FRAME_SIZE_PIXELS];
+        let mut ntsc_frame = vec![0; NTSC_FRAME_SIZE_BYTES];
+
+        for frame_num in 0..self.frames {
+            let input = self.movie.get(frame_num).copied().unwrap_or_default();
+            if input.reset {
+                nes.reset();
+            }
+            nes.set_buttons(input.buttons);
+
+            match &mut ntsc_filter {
+                Some(filter) => {
+                    nes.frame_indices(&mut frame_indices);
+                    filter.filter(&frame_indices, &mut ntsc_frame);
+                    recorder.frame(&ntsc_frame);
+                }
+                None => {
+                    nes.frame(&mut frame);
+                    recorder.frame(&frame);
+                }
+            }
+        }
+
+        let (rgb, width) = match ntsc_filter {
+            Some(_) => (&ntsc_frame[..], NTSC_WIDTH as u32),
+            None => (&frame[..], DISPLAY_WIDTH),
+        };
+
+        if let Some(path) = &self.screenshot {
+            fs::write(path, png_encode(rgb, width, DISPLAY_HEIGHT)).unwrap_or_else(|err| {
+                eprintln!("Error saving screenshot {}: {}", path, err);
+                process::exit(1);
+            });
+        }
+
+        if self.hash {
+            println!("{:08X}", crc32(rgb));
+        }
+
+        true
+    }
+}
+
+/// Uses the patch given on the command line, otherwise looks for one next to
+/// the ROM with the same name.
+fn find_patch(args: &[String]) -> Option<String> {
+    if let Some(path) = args.get(2) {
+        return Some(path.clone());
+    }
+
+    PATCH_EXTENSIONS
+        .iter()
+        .map(|ext| Path::new(&args[1]).with_extension(ext))
+        .find(|path| path.is_file())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Dumps every frame to `--record-video=<file.y4m>`. `--record-audio` is
+/// refused until there's an APU to record.
+struct Recorder {
+    video: Option<Y4mWriter<BufWriter<File>>>,
+}
+
+impl Recorder {
+    fn from_flags(flags: &[String], region: Region, width: u32) -> Self {
+        let flag_val = |name: &str| flags.iter().find_map(|flag| flag.strip_prefix(name));
+        let exit = |path: &str, err: std::io::Error| -> ! {
+            eprintln!("Error recording to {}: {}", path, err);
+            process::exit(1);
+        };
+
+        if flag_val("--record-audio=").is_some() {
+            eprintln!("Audio is not emulated yet, so it can't be recorded");
+            process::exit(1);
+        }
+
+        let video = flag_val("--record-video=").map(|path| {
+            File::create(path)
+                .and_then(|file| {
+                    Y4mWriter::init(BufWriter::new(file), width, DISPLAY_HEIGHT, region)
+                })
+                .unwrap_or_else(|err| exit(path, err))
+        });
+
+        Self { video }
+    }
+
+    fn frame(&mut self, rgb: &[u8]) {
+        if let Some(video) = &mut self.video {
+            if let Err(err) = video.write_frame(rgb) {
+                eprintln!("Error recording video: {}", err);
+                self.video = None;
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(video) = self.video.take() {
+            if let Err(err) = video.finish() {
+                eprintln!("Error recording video: {}", err);
+            }
+        }
+    }
+}
+
+/// Screenshots are saved next to the ROM, named after it and the current
+/// time in UTC, e.g. `game-20240131-235959-123.png`.
+#[cfg(feature = "window")]
+fn screenshot_path(rom_path: &str) -> PathBuf {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = time.as_secs();
+
+    // Converts days since 1970-01-01 to a date in the proleptic Gregorian
+    // calendar, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_idx = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_idx + 2) / 5 + 1;
+    let month = if month_idx < 10 {
+        month_idx + 3
+    } else {
+        month_idx - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    let rom_path = Path::new(rom_path);
+    let name = format!(
+        "{}-{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}.png",
+        rom_path.file_stem().unwrap_or_default().to_string_lossy(),
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_millis()
+    );
+    rom_path.with_file_name(name)
+}
+
+#[cfg(feature = "window")]
+fn nsf_title(nsf: &Nsf, song: u8) -> String {
+    format!(
+        "{} - {} - {} ({} / {})",
+        WINDOW_TITLE,
+        nsf.title,
+        nsf.artist,
+        song + 1,
+        nsf.num_songs
+    )
+}
@@ -145,6 +145,10 @@ impl Window {
         events
     }
 
+    pub fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
     pub fn closed(&self) -> bool {
         self.window.should_close()
     }
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod test;
+
+use crate::region::Region;
+use std::io::{self, Seek, SeekFrom, Write};
+
+pub const WAV_SAMPLE_RATE: u32 = 48000;
+const WAV_HEADER_SIZE: u32 = 44;
+const WAV_FORMAT_PCM: u16 = 1;
+const WAV_CHANNELS: u16 = 1;
+const WAV_BITS_PER_SAMPLE: u16 = 16;
+
+/// Writes frames as an uncompressed YUV4MPEG2 stream at the region's exact
+/// frame rate, converting RGB to 4:2:0 BT.601 YCbCr.
+pub struct Y4mWriter<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// `width` and `height` must be even.
+    pub fn init(mut out: W, width: u32, height: u32, region: Region) -> io::Result<Self> {
+        let (fps_num, fps_den) = region.frame_rate();
+        writeln!(
+            out,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420jpeg",
+            width, height, fps_num, fps_den
+        )?;
+
+        Ok(Self {
+            out,
+            width: width as usize,
+            height: height as usize,
+        })
+    }
+
+    pub fn write_frame(&mut self, rgb: &[u8]) -> io::Result<()> {
+        let (width, height) = (self.width, self.height);
+        let pixel = |x: usize, y: usize| {
+            let idx = (y * width + x) * 3;
+            (rgb[idx] as i32, rgb[idx + 1] as i32, rgb[idx + 2] as i32)
+        };
+
+        let mut luma = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g, b) = pixel(x, y);
+                luma.push((((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8);
+            }
+        }
+
+        let mut cb = Vec::with_capacity(width * height / 4);
+        let mut cr = Vec::with_capacity(width * height / 4);
+        for y in (0..height).step_by(2) {
+            for x in (0..width).step_by(2) {
+                let (r, g, b) = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                    .iter()
+                    .map(|&(dx, dy)| pixel(x + dx, y + dy))
+                    .fold((0, 0, 0), |acc, px| {
+                        (acc.0 + px.0, acc.1 + px.1, acc.2 + px.2)
+                    });
+                let (r, g, b) = (r / 4, g / 4, b / 4);
+
+                cb.push((((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8);
+                cr.push((((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8);
+            }
+        }
+
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&luma)?;
+        self.out.write_all(&cb)?;
+        self.out.write_all(&cr)
+    }
+
+    /// Flushes the stream, which with a `BufWriter` is the only way to see
+    /// errors writing the last frames.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Writes 16-bit mono PCM. The sizes in the header are filled in by
+/// `finish`.
+pub struct WavWriter<W: Write + Seek> {
+    out: W,
+    num_samples: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn init(mut out: W) -> io::Result<Self> {
+        let block_align = WAV_CHANNELS * WAV_BITS_PER_SAMPLE / 8;
+
+        out.write_all(b"RIFF")?;
+        out.write_all(&(WAV_HEADER_SIZE - 8).to_le_bytes())?;
+        out.write_all(b"WAVEfmt ")?;
+        out.write_all(&16u32.to_le_bytes())?;
+        out.write_all(&WAV_FORMAT_PCM.to_le_bytes())?;
+        out.write_all(&WAV_CHANNELS.to_le_bytes())?;
+        out.write_all(&WAV_SAMPLE_RATE.to_le_bytes())?;
+        out.write_all(&(WAV_SAMPLE_RATE * block_align as u32).to_le_bytes())?;
+        out.write_all(&block_align.to_le_bytes())?;
+        out.write_all(&WAV_BITS_PER_SAMPLE.to_le_bytes())?;
+        out.write_all(b"data")?;
+        out.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            out,
+            num_samples: 0,
+        })
+    }
+
+    pub fn write_samples(&mut self, samples: &[i16]) -> io::Result<()> {
+        for sample in samples {
+            self.out.write_all(&sample.to_le_bytes())?;
+        }
+        self.num_samples += samples.len() as u32;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        let data_size = self.num_samples * (WAV_BITS_PER_SAMPLE / 8) as u32;
+
+        self.out.seek(SeekFrom::Start(4))?;
+        self.out
+            .write_all(&(WAV_HEADER_SIZE - 8 + data_size).to_le_bytes())?;
+        self.out.seek(SeekFrom::Start(WAV_HEADER_SIZE as u64 - 4))?;
+        self.out.write_all(&data_size.to_le_bytes())?;
+        self.out.seek(SeekFrom::End(0))?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// How many audio samples have played by the end of `frames` frames, so
+/// audio written a frame at a time stays in sync with the video.
+pub fn samples_at_frame(region: Region, frames: u64) -> u64 {
+    let (fps_num, fps_den) = region.frame_rate();
+    frames * WAV_SAMPLE_RATE as u64 * fps_den / fps_num
+}
@@ -0,0 +1,68 @@
+use super::*;
+use std::io::{BufWriter, Cursor, ErrorKind};
+
+struct FullDisk;
+
+impl Write for FullDisk {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::from(ErrorKind::StorageFull))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn y4m() {
+    let mut rgb = vec![0; 4 * 2 * 3];
+    rgb[..3].copy_from_slice(&[0xFF, 0xFF, 0xFF]);
+    rgb[12..15].copy_from_slice(&[0xFF, 0x00, 0x00]);
+
+    let mut y4m = Y4mWriter::init(vec![], 4, 2, Region::Ntsc).unwrap();
+    y4m.write_frame(&rgb).unwrap();
+    y4m.write_frame(&rgb).unwrap();
+
+    let y4m = y4m.finish().unwrap();
+
+    let header = b"YUV4MPEG2 W4 H2 F39375000:655171 Ip A1:1 C420jpeg\n";
+    assert!(y4m.starts_with(header));
+
+    let frame = &y4m[header.len()..];
+    let frame_size = b"FRAME\n".len() + 8 + 2 + 2;
+    assert_eq!(frame.len(), frame_size * 2);
+    assert_eq!(
+        frame[..frame_size],
+        *b"FRAME\n\xEB\x10\x10\x10\x52\x10\x10\x10\x77\x80\x9C\x80"
+    );
+}
+
+#[test]
+fn y4m_finish_error() {
+    let mut y4m = Y4mWriter::init(BufWriter::new(FullDisk), 4, 2, Region::Pal).unwrap();
+    y4m.write_frame(&[0; 4 * 2 * 3]).unwrap();
+    assert!(y4m.finish().is_err());
+}
+
+#[test]
+fn wav() {
+    let mut wav = WavWriter::init(Cursor::new(vec![])).unwrap();
+    wav.write_samples(&[0, -1, 0x1234]).unwrap();
+    let data = wav.finish().unwrap().into_inner();
+
+    assert_eq!(data.len(), WAV_HEADER_SIZE as usize + 6);
+    assert_eq!(data[..4], *b"RIFF");
+    assert_eq!(data[4..8], (36u32 + 6).to_le_bytes());
+    assert_eq!(data[24..28], WAV_SAMPLE_RATE.to_le_bytes());
+    assert_eq!(data[40..44], 6u32.to_le_bytes());
+    assert_eq!(data[44..], [0x00, 0x00, 0xFF, 0xFF, 0x34, 0x12]);
+}
+
+#[test]
+fn audio_sync() {
+    assert_eq!(samples_at_frame(Region::Ntsc, 0), 0);
+    assert_eq!(samples_at_frame(Region::Ntsc, 1), 798);
+
+    // 3000 PAL frames are slightly under a minute.
+    assert_eq!(samples_at_frame(Region::Pal, 3000), 2_879_598);
+}
@@ -13,20 +13,41 @@ pub mod mapper3;
 #[path = "mapper4.rs"]
 pub mod mapper4;
 
-use crate::rom::Rom;
+#[path = "mapper_nsf.rs"]
+pub mod mapper_nsf;
+
+#[cfg(test)]
+mod test;
+
+use crate::error::NesError;
+use crate::nsf::Nsf;
+use crate::region::Region;
+use crate::rom::{Rom, MAPPER_FDS};
+use crate::{KB_1, KB_32, KB_4, KB_8};
+use lazy_static::lazy_static;
 use mapper0::Mapper0;
 use mapper1::Mapper1;
 use mapper2::Mapper2;
 use mapper3::Mapper3;
 use mapper4::Mapper4;
+use mapper_nsf::{nsf_prg_rom, MapperNsf};
+use std::collections::HashMap;
+use std::sync::Mutex;
 use NametableConf::*;
 
+pub const EXP_START: u16 = 0x4020;
+pub const EXP_END: u16 = 0x5FFF;
 pub const PRG_RAM_START: u16 = 0x6000;
 pub const PRG_RAM_END: u16 = 0x7FFF;
+pub const TRAINER_START: u16 = 0x7000;
 pub const PRG_ROM_START: u16 = 0x8000;
 pub const PRG_ROM_END: u16 = 0xFFFF;
 pub const PATTERN_START: u16 = 0x0000;
 pub const PATTERN_END: u16 = 0x1FFF;
+pub const PRG_PAGE_SIZE: usize = KB_4;
+pub const CHR_PAGE_SIZE: usize = KB_1;
+pub const NUM_PRG_PAGES: usize = 8;
+pub const NUM_CHR_PAGES: usize = 8;
 pub const VRAM_SIZE: usize = 0x800;
 pub const NAMETABLE_SIZE: u16 = (VRAM_SIZE / 2) as u16;
 pub const NAMETABLE_USIZE: usize = NAMETABLE_SIZE as usize;
@@ -39,19 +60,92 @@ pub const NAMETABLE_2_END: u16 = NAMETABLE_2_START + (NAMETABLE_SIZE - 1);
 pub const NAMETABLE_3_START: u16 = NAMETABLE_2_END + 1;
 pub const NAMETABLE_3_END: u16 = NAMETABLE_3_START + (NAMETABLE_SIZE - 1);
 
+/// Constructs a mapper for the given ROM. Registered with `register_mapper`.
+pub type MapperCtor = fn(&Rom) -> Box<dyn Mapper>;
+
+lazy_static! {
+    static ref MAPPER_REGISTRY: Mutex<HashMap<(u16, Option<u8>), MapperCtor>> = {
+        let builtins: [(u16, MapperCtor); 5] = [
+            (0, |_| Box::new(Mapper0::init())),
+            (1, |_| Box::new(Mapper1::init())),
+            (2, |_| Box::new(Mapper2::init())),
+            (3, |_| Box::new(Mapper3::init())),
+            (4, |_| Box::new(Mapper4::init())),
+        ];
+
+        Mutex::new(
+            builtins
+                .iter()
+                .map(|&(mapper, ctor)| ((mapper, None), ctor))
+                .collect(),
+        )
+    };
+}
+
+/// Registers a constructor for a mapper number, replacing any existing one.
+/// With `submapper` set to `None` the constructor is used for every submapper
+/// that doesn't have its own entry. Must be called before `Nes::init`.
+pub fn register_mapper(mapper: u16, submapper: Option<u8>, ctor: MapperCtor) {
+    MAPPER_REGISTRY
+        .lock()
+        .unwrap()
+        .insert((mapper, submapper), ctor);
+}
+
+fn lookup_mapper(mapper: u16, submapper: u8) -> Option<MapperCtor> {
+    let registry = MAPPER_REGISTRY.lock().unwrap();
+
+    registry
+        .get(&(mapper, Some(submapper)))
+        .or_else(|| registry.get(&(mapper, None)))
+        .copied()
+}
+
 #[derive(Copy, Clone, PartialEq)]
-enum NametableConf {
+pub enum NametableConf {
     Horizontal,
     Vertical,
     OneScreenLower,
     OneScreenUpper,
 }
 
-struct CartData<'a> {
-    prg_rom_size: usize,
-    chr_size: usize,
-    nt_conf: &'a mut NametableConf,
-    irq: &'a mut bool,
+/// Cartridge state that mappers are allowed to see and modify.
+///
+/// PRG and CHR are mapped through page tables holding the offset of each
+/// 4 KB CPU page ($8000-$FFFF) and 1 KB PPU page ($0000-$1FFF). Mappers
+/// update the tables through `map_prg`/`map_chr` when their bank registers
+/// change, so reads are a single lookup.
+pub struct CartData<'a> {
+    pub prg_rom_size: usize,
+    pub chr_size: usize,
+    pub nt_conf: &'a mut NametableConf,
+    pub irq: &'a mut bool,
+    pub prg_pages: &'a mut [usize; NUM_PRG_PAGES],
+    pub chr_pages: &'a mut [usize; NUM_CHR_PAGES],
+}
+
+impl CartData<'_> {
+    /// Maps the `size`-byte PRG ROM bank `bank` into the CPU window starting
+    /// at `addr`. Banks past the end of PRG ROM wrap around.
+    pub fn map_prg(&mut self, addr: u16, size: usize, bank: usize) {
+        let first_page = (addr - PRG_ROM_START) as usize / PRG_PAGE_SIZE;
+
+        for page in 0..(size / PRG_PAGE_SIZE) {
+            self.prg_pages[first_page + page] =
+                wrap((bank * size) + (page * PRG_PAGE_SIZE), self.prg_rom_size);
+        }
+    }
+
+    /// Maps the `size`-byte CHR bank `bank` into the PPU window starting at
+    /// `addr`. Banks past the end of CHR ROM/RAM wrap around.
+    pub fn map_chr(&mut self, addr: u16, size: usize, bank: usize) {
+        let first_page = addr as usize / CHR_PAGE_SIZE;
+
+        for page in 0..(size / CHR_PAGE_SIZE) {
+            self.chr_pages[first_page + page] =
+                wrap((bank * size) + (page * CHR_PAGE_SIZE), self.chr_size);
+        }
+    }
 }
 
 macro_rules! cart_data {
@@ -61,22 +155,50 @@ macro_rules! cart_data {
             chr_size: $cart.chr.len(),
             nt_conf: &mut $cart.nt_conf,
             irq: &mut $cart.irq,
+            prg_pages: &mut $cart.prg_pages,
+            chr_pages: &mut $cart.chr_pages,
         }
     };
 }
 
-trait Mapper {
+pub trait Mapper {
+    /// Overrides a CPU read anywhere in $4020-$FFFF.
+    fn read_reg(&mut self, addr: u16, cart: &mut CartData) -> Option<u8> {
+        None
+    }
+
+    /// Handles a CPU write to $8000-$FFFF.
     fn write_reg(&mut self, addr: u16, data: u8, cart: &mut CartData) {}
 
-    fn map_prg(&mut self, addr: u16, cart: &mut CartData) -> usize {
-        (addr - PRG_ROM_START) as usize % cart.prg_rom_size
+    /// Handles a CPU write to $4020-$5FFF.
+    fn write_exp(&mut self, addr: u16, data: u8, cart: &mut CartData) {}
+
+    /// Sets up the initial page tables. By default the first 32 KB of PRG ROM
+    /// (mirrored if smaller) and the first 8 KB of CHR are mapped.
+    fn power_on(&mut self, cart: &mut CartData) {
+        cart.map_prg(PRG_ROM_START, KB_32, 0);
+        cart.map_chr(PATTERN_START, KB_8, 0);
     }
 
-    fn map_chr(&mut self, addr: u16, cart: &mut CartData) -> usize {
-        addr as usize
+    /// Whether `ppu_addr` should be called on every pattern table access.
+    fn watches_ppu_addr(&self) -> bool {
+        false
     }
 
-    fn tick(&mut self) {}
+    /// Observes a PPU pattern table access, e.g. to clock a scanline counter
+    /// from A12.
+    fn ppu_addr(&mut self, addr: u16, cart: &mut CartData) {}
+
+    /// Called once per CPU cycle.
+    fn tick(&mut self, cart: &mut CartData) {}
+
+    /// Non-volatile state kept on the board itself (e.g. an EEPROM), saved
+    /// alongside PRG RAM.
+    fn save_data(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {}
 }
 
 pub struct Cartridge {
@@ -88,38 +210,124 @@ pub struct Cartridge {
     vram: [u8; VRAM_SIZE],
     mapper: Box<dyn Mapper>,
     irq: bool,
+    prg_pages: [usize; NUM_PRG_PAGES],
+    chr_pages: [usize; NUM_CHR_PAGES],
+    watches_ppu_addr: bool,
 }
 
 impl Cartridge {
-    pub fn init(rom: &Rom) -> Self {
-        Self {
-            prg_rom: rom.prg_rom.clone(),
-            prg_ram: vec![0; rom.prg_ram_size as usize],
-            chr: if rom.chr_ram_size == 0 {
+    pub fn init(rom: &Rom) -> Result<Self, NesError> {
+        if rom.mapper == MAPPER_FDS {
+            return Err(NesError::MissingBios);
+        }
+
+        let mapper = match lookup_mapper(rom.mapper, rom.submapper) {
+            Some(ctor) => ctor(rom),
+            None => {
+                return Err(NesError::UnsupportedMapper {
+                    mapper: rom.mapper,
+                    submapper: rom.submapper,
+                })
+            }
+        };
+
+        if rom.prg_rom.is_empty() || !rom.prg_rom.len().is_multiple_of(PRG_PAGE_SIZE) {
+            return Err(NesError::BadSize {
+                region: "PRG ROM",
+                size: rom.prg_rom.len(),
+            });
+        }
+
+        if !rom.chr_rom.len().is_multiple_of(CHR_PAGE_SIZE) {
+            return Err(NesError::BadSize {
+                region: "CHR ROM",
+                size: rom.chr_rom.len(),
+            });
+        }
+
+        // A NES 2.0 header can give neither CHR ROM nor CHR RAM, which no
+        // board has, so it gets the 8 KB of CHR RAM an iNES header implies.
+        let chr_ram_size = match rom.chr_ram_size + rom.chr_nvram_size {
+            0 if rom.chr_rom.is_empty() => KB_8,
+            size => size,
+        };
+
+        if !chr_ram_size.is_multiple_of(CHR_PAGE_SIZE) {
+            return Err(NesError::BadSize {
+                region: "CHR RAM",
+                size: chr_ram_size,
+            });
+        }
+
+        let mut prg_ram = vec![0; rom.prg_ram_size + rom.prg_nvram_size];
+
+        if let Some(trainer) = &rom.trainer {
+            let trainer_offset = (TRAINER_START - PRG_RAM_START) as usize;
+            prg_ram.resize(prg_ram.len().max(KB_8), 0);
+            prg_ram[trainer_offset..trainer_offset + trainer.len()].copy_from_slice(trainer);
+        }
+
+        Ok(Self::init_with_mapper(
+            rom.prg_rom.clone(),
+            prg_ram,
+            if chr_ram_size == 0 {
                 rom.chr_rom.clone()
             } else {
-                vec![0; rom.chr_ram_size as usize]
+                vec![0; chr_ram_size]
             },
-            chr_ram: rom.chr_ram_size > 0,
-            nt_conf: if rom.vert_mirrored {
+            chr_ram_size > 0,
+            if rom.vert_mirrored {
                 Vertical
             } else {
                 Horizontal
             },
+            mapper,
+        ))
+    }
+
+    pub fn init_nsf(nsf: &Nsf, song: u8, region: Region) -> Self {
+        Self::init_with_mapper(
+            nsf_prg_rom(nsf),
+            vec![0; KB_8],
+            vec![0; KB_8],
+            true,
+            Vertical,
+            Box::new(MapperNsf::init(nsf, song, region)),
+        )
+    }
+
+    fn init_with_mapper(
+        prg_rom: Vec<u8>,
+        prg_ram: Vec<u8>,
+        chr: Vec<u8>,
+        chr_ram: bool,
+        nt_conf: NametableConf,
+        mapper: Box<dyn Mapper>,
+    ) -> Self {
+        let mut cart = Self {
+            prg_rom,
+            prg_ram,
+            chr,
+            chr_ram,
+            nt_conf,
             vram: [0x00; VRAM_SIZE],
-            mapper: match rom.mapper {
-                0 => Box::new(Mapper0::init()),
-                1 => Box::new(Mapper1::init()),
-                2 => Box::new(Mapper2::init()),
-                3 => Box::new(Mapper3::init()),
-                4 => Box::new(Mapper4::init()),
-                _ => panic!("Invalid or unsupported mapper: {}", rom.mapper),
-            },
+            watches_ppu_addr: mapper.watches_ppu_addr(),
+            mapper,
             irq: false,
-        }
+            prg_pages: [0; NUM_PRG_PAGES],
+            chr_pages: [0; NUM_CHR_PAGES],
+        };
+
+        cart.mapper.power_on(cart_data!(cart));
+
+        cart
     }
 
     pub fn cpu_read(&mut self, addr: u16) -> u8 {
+        if let Some(data) = self.mapper.read_reg(addr, cart_data!(self)) {
+            return data;
+        }
+
         match addr {
             PRG_RAM_START..=PRG_RAM_END => {
                 if self.prg_ram.len() == 0 {
@@ -128,9 +336,7 @@ impl Cartridge {
                     self.prg_ram[(addr - PRG_RAM_START) as usize % self.prg_ram.len()]
                 }
             }
-            PRG_ROM_START..=PRG_ROM_END => {
-                self.prg_rom[self.mapper.map_prg(addr, cart_data!(self))]
-            }
+            PRG_ROM_START..=PRG_ROM_END => self.prg_rom[self.prg_idx(addr)],
             _ => 0x00,
         }
     }
@@ -145,13 +351,19 @@ impl Cartridge {
                 }
             }
             PRG_ROM_START..=PRG_ROM_END => self.mapper.write_reg(addr, data, cart_data!(self)),
+            EXP_START..=EXP_END => self.mapper.write_exp(addr, data, cart_data!(self)),
             _ => (),
         }
     }
 
     pub fn ppu_read(&mut self, addr: u16) -> u8 {
         match addr {
-            PATTERN_START..=PATTERN_END => self.chr[self.mapper.map_chr(addr, cart_data!(self))],
+            PATTERN_START..=PATTERN_END => {
+                if self.watches_ppu_addr {
+                    self.mapper.ppu_addr(addr, cart_data!(self));
+                }
+                self.chr[self.chr_idx(addr)]
+            }
             NAMETABLE_0_START..=NAMETABLE_3_END => self.vram[vram_idx(addr, self.nt_conf)],
             _ => 0x00,
         }
@@ -160,9 +372,12 @@ impl Cartridge {
     pub fn ppu_write(&mut self, addr: u16, data: u8) {
         match addr {
             PATTERN_START..=PATTERN_END => {
+                if self.watches_ppu_addr {
+                    self.mapper.ppu_addr(addr, cart_data!(self));
+                }
                 if self.chr_ram {
-                    let cart_data = cart_data!(self);
-                    self.chr[self.mapper.map_chr(addr, cart_data)] = data;
+                    let chr_idx = self.chr_idx(addr);
+                    self.chr[chr_idx] = data;
                 }
             }
             NAMETABLE_0_START..=NAMETABLE_3_END => self.vram[vram_idx(addr, self.nt_conf)] = data,
@@ -170,12 +385,42 @@ impl Cartridge {
         }
     }
 
+    pub fn save_data(&self) -> Vec<u8> {
+        let mut data = self.prg_ram.clone();
+        data.extend(self.mapper.save_data());
+        data
+    }
+
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        let prg_ram_size = self.prg_ram.len().min(data.len());
+        self.prg_ram[..prg_ram_size].copy_from_slice(&data[..prg_ram_size]);
+        self.mapper.load_save_data(&data[prg_ram_size..]);
+    }
+
     pub fn irq(&self) -> bool {
         self.irq
     }
 
     pub fn tick(&mut self) {
-        self.mapper.tick();
+        self.mapper.tick(cart_data!(self));
+    }
+
+    fn prg_idx(&self, addr: u16) -> usize {
+        let addr = (addr - PRG_ROM_START) as usize;
+        self.prg_pages[addr / PRG_PAGE_SIZE] + (addr % PRG_PAGE_SIZE)
+    }
+
+    fn chr_idx(&self, addr: u16) -> usize {
+        let addr = addr as usize;
+        self.chr_pages[addr / CHR_PAGE_SIZE] + (addr % CHR_PAGE_SIZE)
+    }
+}
+
+fn wrap(offset: usize, size: usize) -> usize {
+    if size == 0 {
+        0
+    } else {
+        offset % size
     }
 }
 
@@ -30,11 +30,15 @@ pub struct Mapper1 {
     chr_bank_1: u8,
     prg_bank: u8,
     written_this_cycle: bool,
+    written_last_cycle: bool,
 }
 
 impl Mapper for Mapper1 {
+    /// Writes on the cycle after another write are ignored, so only the
+    /// first write of a read-modify-write instruction counts.
     fn write_reg(&mut self, addr: u16, data: u8, cart: &mut CartData) {
-        if self.written_this_cycle {
+        self.written_this_cycle = true;
+        if self.written_last_cycle {
             return;
         }
 
@@ -69,50 +73,15 @@ impl Mapper for Mapper1 {
             }
         }
 
-        self.written_this_cycle = true;
+        self.update_banks(cart);
     }
 
-    fn map_prg(&mut self, addr: u16, cart: &mut CartData) -> usize {
-        let prg_bank = self.prg_bank as usize;
-        let offset = (addr as usize) % KB_32;
-
-        (match self.ctrl.prg_bank_mode() {
-            0 | 1 => ((prg_bank >> 1) * KB_32) + offset,
-            2 => {
-                if offset < KB_16 {
-                    offset
-                } else {
-                    (prg_bank * KB_16) + (offset % KB_16)
-                }
-            }
-            3 => {
-                if offset < KB_16 {
-                    (prg_bank * KB_16) + offset
-                } else {
-                    (cart.prg_rom_size - KB_16) + (offset % KB_16)
-                }
-            }
-            _ => panic!(),
-        }) % cart.prg_rom_size
+    fn power_on(&mut self, cart: &mut CartData) {
+        self.update_banks(cart);
     }
 
-    fn map_chr(&mut self, addr: u16, cart: &mut CartData) -> usize {
-        let chr_bank_0 = self.chr_bank_0 as usize;
-        let chr_bank_1 = self.chr_bank_1 as usize;
-        let offset = (addr as usize) % KB_8;
-
-        (if self.ctrl.chr_bank_mode() == 0 {
-            ((chr_bank_0 >> 1) * KB_8) + offset
-        } else {
-            if offset < KB_4 {
-                (chr_bank_0 * KB_4) + offset
-            } else {
-                (chr_bank_1 * KB_4) + (offset % KB_4)
-            }
-        }) % cart.chr_size
-    }
-
-    fn tick(&mut self) {
+    fn tick(&mut self, cart: &mut CartData) {
+        self.written_last_cycle = self.written_this_cycle;
         self.written_this_cycle = false;
     }
 }
@@ -127,6 +96,37 @@ impl Mapper1 {
             chr_bank_1: 0,
             prg_bank: 0,
             written_this_cycle: false,
+            written_last_cycle: false,
+        }
+    }
+
+    fn update_banks(&mut self, cart: &mut CartData) {
+        let prg_bank = self.prg_bank as usize;
+        let chr_bank_0 = self.chr_bank_0 as usize;
+        let chr_bank_1 = self.chr_bank_1 as usize;
+
+        match self.ctrl.prg_bank_mode() {
+            0 | 1 => cart.map_prg(PRG_ROM_START, KB_32, prg_bank >> 1),
+            2 => {
+                cart.map_prg(PRG_ROM_START, KB_16, 0);
+                cart.map_prg(PRG_ROM_START + KB_16 as u16, KB_16, prg_bank);
+            }
+            3 => {
+                cart.map_prg(PRG_ROM_START, KB_16, prg_bank);
+                cart.map_prg(
+                    PRG_ROM_START + KB_16 as u16,
+                    KB_16,
+                    (cart.prg_rom_size / KB_16) - 1,
+                );
+            }
+            _ => panic!(),
+        }
+
+        if self.ctrl.chr_bank_mode() == 0 {
+            cart.map_chr(PATTERN_START, KB_8, chr_bank_0 >> 1);
+        } else {
+            cart.map_chr(PATTERN_START, KB_4, chr_bank_0);
+            cart.map_chr(PATTERN_START + KB_4 as u16, KB_4, chr_bank_1);
         }
     }
 }
@@ -1,33 +1,26 @@
 use super::*;
 use crate::{KB_16, KB_8};
 
-pub struct Mapper2 {
-    prg_offset: usize,
-}
+pub struct Mapper2 {}
 
 impl Mapper for Mapper2 {
     fn write_reg(&mut self, addr: u16, data: u8, cart: &mut CartData) {
-        self.prg_offset = ((data as usize) * KB_16) % cart.prg_rom_size
+        cart.map_prg(PRG_ROM_START, KB_16, data as usize);
     }
 
-    fn map_prg(&mut self, addr: u16, cart: &mut CartData) -> usize {
-        let bank = (addr - PRG_ROM_START) as usize / KB_16;
-        let mut offset = (addr - PRG_ROM_START) as usize % KB_16;
-
-        offset += if bank == 0 {
-            self.prg_offset
-        } else if bank == 1 {
-            cart.prg_rom_size - KB_16
-        } else {
-            panic!("mapper 2: invalid address {:04X}", bank);
-        };
-
-        offset
+    fn power_on(&mut self, cart: &mut CartData) {
+        cart.map_prg(PRG_ROM_START, KB_16, 0);
+        cart.map_prg(
+            PRG_ROM_START + KB_16 as u16,
+            KB_16,
+            (cart.prg_rom_size / KB_16) - 1,
+        );
+        cart.map_chr(PATTERN_START, KB_8, 0);
     }
 }
 
 impl Mapper2 {
     pub fn init() -> Mapper2 {
-        Mapper2 { prg_offset: 0 }
+        Mapper2 {}
     }
 }
@@ -61,74 +61,23 @@ impl Mapper for Mapper4 {
             (3, 1) => self.irq_enable = true,
             _ => {}
         }
+
+        self.update_banks(cart);
     }
 
-    fn map_prg(&mut self, addr: u16, cart: &mut CartData) -> usize {
-        let prg_bank_0 = self.prg_bank_0 as usize;
-        let prg_bank_1 = self.prg_bank_1 as usize;
-        let prg_bank_last = (cart.prg_rom_size / KB_8) - 1;
-        let prg_bank_second_last = prg_bank_last - 1;
-        let offset = (addr as usize) % KB_8;
-        let base = match (addr % PRG_ROM_START) / KB_8 as u16 {
-            0 => {
-                if self.bank_select.prg_mode() == 0 {
-                    prg_bank_0
-                } else {
-                    prg_bank_second_last
-                }
-            }
-            1 => prg_bank_1,
-            2 => {
-                if self.bank_select.prg_mode() == 0 {
-                    prg_bank_second_last
-                } else {
-                    prg_bank_0
-                }
-            }
-            3 => prg_bank_last,
-            _ => panic!(),
-        } * KB_8;
+    fn power_on(&mut self, cart: &mut CartData) {
+        self.update_banks(cart);
+    }
 
-        (base + offset) % cart.prg_rom_size
+    fn watches_ppu_addr(&self) -> bool {
+        true
     }
 
-    fn map_chr(&mut self, addr: u16, cart: &mut CartData) -> usize {
+    fn ppu_addr(&mut self, addr: u16, cart: &mut CartData) {
         self.update_irq(addr, cart.irq);
-        let addr = addr as usize;
-        let offset = addr % KB_4;
-        let chr_1kb_bank_0 = (self.chr_1kb_bank_0 as usize, KB_1);
-        let chr_1kb_bank_1 = (self.chr_1kb_bank_1 as usize, KB_1);
-        let chr_1kb_bank_2 = (self.chr_1kb_bank_2 as usize, KB_1);
-        let chr_1kb_bank_3 = (self.chr_1kb_bank_3 as usize, KB_1);
-        let chr_2kb_bank_0 = ((self.chr_2kb_bank_0 >> 1) as usize, KB_2);
-        let chr_2kb_bank_1 = ((self.chr_2kb_bank_1 >> 1) as usize, KB_2);
-
-        let (base, size) = if ((addr / KB_4) == 0 && self.bank_select.chr_mode() == 0)
-            || ((addr / KB_4) == 1 && self.bank_select.chr_mode() == 1)
-        {
-            if (offset / KB_2) == 0 {
-                chr_2kb_bank_0
-            } else {
-                chr_2kb_bank_1
-            }
-        } else if ((addr / KB_4) == 0 && self.bank_select.chr_mode() == 1)
-            || ((addr / KB_4) == 1 && self.bank_select.chr_mode() == 0)
-        {
-            match offset / KB_1 {
-                0 => chr_1kb_bank_0,
-                1 => chr_1kb_bank_1,
-                2 => chr_1kb_bank_2,
-                3 => chr_1kb_bank_3,
-                _ => panic!(),
-            }
-        } else {
-            panic!()
-        };
-
-        ((base * size) + (addr % size)) % cart.chr_size
     }
 
-    fn tick(&mut self) {
+    fn tick(&mut self, cart: &mut CartData) {
         self.irq_delay_counter += 1
     }
 }
@@ -154,6 +103,38 @@ impl Mapper4 {
         }
     }
 
+    fn update_banks(&mut self, cart: &mut CartData) {
+        let prg_bank_last = (cart.prg_rom_size / KB_8) - 1;
+        let prg_bank_second_last = prg_bank_last - 1;
+        let (prg_bank_8000, prg_bank_c000) = if self.bank_select.prg_mode() == 0 {
+            (self.prg_bank_0 as usize, prg_bank_second_last)
+        } else {
+            (prg_bank_second_last, self.prg_bank_0 as usize)
+        };
+
+        cart.map_prg(0x8000, KB_8, prg_bank_8000);
+        cart.map_prg(0xA000, KB_8, self.prg_bank_1 as usize);
+        cart.map_prg(0xC000, KB_8, prg_bank_c000);
+        cart.map_prg(0xE000, KB_8, prg_bank_last);
+
+        let (chr_2kb_start, chr_1kb_start) = if self.bank_select.chr_mode() == 0 {
+            (0x0000, 0x1000)
+        } else {
+            (0x1000, 0x0000)
+        };
+
+        cart.map_chr(chr_2kb_start, KB_2, (self.chr_2kb_bank_0 >> 1) as usize);
+        cart.map_chr(
+            chr_2kb_start + 0x0800,
+            KB_2,
+            (self.chr_2kb_bank_1 >> 1) as usize,
+        );
+        cart.map_chr(chr_1kb_start, KB_1, self.chr_1kb_bank_0 as usize);
+        cart.map_chr(chr_1kb_start + 0x0400, KB_1, self.chr_1kb_bank_1 as usize);
+        cart.map_chr(chr_1kb_start + 0x0800, KB_1, self.chr_1kb_bank_2 as usize);
+        cart.map_chr(chr_1kb_start + 0x0C00, KB_1, self.chr_1kb_bank_3 as usize);
+    }
+
     fn update_irq(&mut self, addr: u16, irq: &mut bool) {
         let a12 = addr & 0x1000 == 0x1000;
 
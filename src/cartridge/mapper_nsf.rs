@@ -0,0 +1,177 @@
+use super::*;
+use crate::assemble::assemble;
+use crate::cpu::VEC_NMI;
+use crate::nsf::Nsf;
+use crate::region::Region;
+use crate::{KB_32, KB_4};
+
+const DEFAULT_PLAY_SPEED_NTSC: u16 = 16639;
+const DEFAULT_PLAY_SPEED_PAL: u16 = 19997;
+const NUM_BANKS: usize = 8;
+const BANK_REG_START: u16 = 0x5FF8;
+const BANK_REG_END: u16 = 0x5FFF;
+const DRIVER_REG: u16 = 0x40FF;
+const DRIVER_START: u16 = 0x4100;
+const DRIVER_END: u16 = 0x41FF;
+
+/// Synthetic board for playing NSF tunes. PRG is switched in 4 KB banks
+/// through $5FF8-$5FFF, and a small driver at $4100 calls INIT once and
+/// then PLAY from an IRQ raised at the tune's play rate.
+pub struct MapperNsf {
+    bank_init: [u8; NUM_BANKS],
+    driver: Vec<u8>,
+    vectors: [u8; 6],
+    play_period: u32,
+    play_counter: u32,
+    play_enable: bool,
+}
+
+impl Mapper for MapperNsf {
+    fn read_reg(&mut self, addr: u16, cart: &mut CartData) -> Option<u8> {
+        match addr {
+            DRIVER_START..=DRIVER_END => self.driver.get((addr - DRIVER_START) as usize).copied(),
+            VEC_NMI..=PRG_ROM_END => Some(self.vectors[(addr - VEC_NMI) as usize]),
+            _ => None,
+        }
+    }
+
+    fn write_exp(&mut self, addr: u16, data: u8, cart: &mut CartData) {
+        match addr {
+            DRIVER_REG => {
+                *cart.irq = false;
+                self.play_enable = true;
+            }
+            BANK_REG_START..=BANK_REG_END => {
+                let page = addr - BANK_REG_START;
+                cart.map_prg(PRG_ROM_START + (page * KB_4 as u16), KB_4, data as usize);
+            }
+            _ => {}
+        }
+    }
+
+    fn power_on(&mut self, cart: &mut CartData) {
+        for (page, &bank) in self.bank_init.iter().enumerate() {
+            cart.map_prg(PRG_ROM_START + (page * KB_4) as u16, KB_4, bank as usize);
+        }
+        cart.map_chr(PATTERN_START, KB_8, 0);
+    }
+
+    fn tick(&mut self, cart: &mut CartData) {
+        if !self.play_enable {
+            return;
+        }
+
+        self.play_counter -= 1;
+        if self.play_counter == 0 {
+            self.play_counter = self.play_period;
+            *cart.irq = true;
+        }
+    }
+}
+
+impl MapperNsf {
+    pub fn init(nsf: &Nsf, song: u8, region: Region) -> MapperNsf {
+        let bank_init = if nsf.bankswitched() {
+            nsf.bank_init
+        } else {
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        };
+
+        let play_speed = match (region, nsf.play_speed_ntsc, nsf.play_speed_pal) {
+            (Region::Ntsc, 0, _) => DEFAULT_PLAY_SPEED_NTSC,
+            (Region::Ntsc, speed, _) => speed,
+            (_, _, 0) => DEFAULT_PLAY_SPEED_PAL,
+            (_, _, speed) => speed,
+        };
+        let play_period = ((play_speed as u64 * region.cpu_clock_hz()) / 1_000_000).max(1) as u32;
+
+        let (driver, reset_addr, nmi_addr, irq_addr) = driver(nsf, song, region);
+
+        MapperNsf {
+            bank_init,
+            driver,
+            vectors: [
+                nmi_addr as u8,
+                (nmi_addr >> 8) as u8,
+                reset_addr as u8,
+                (reset_addr >> 8) as u8,
+                irq_addr as u8,
+                (irq_addr >> 8) as u8,
+            ],
+            play_period,
+            play_counter: play_period,
+            play_enable: false,
+        }
+    }
+}
+
+/// Lays out the tune's data as it appears in 4 KB banks. Bankswitched tunes
+/// are padded so that `load_addr` falls at the right offset within bank 0,
+/// everything else is placed directly into a flat 32 KB image.
+pub fn nsf_prg_rom(nsf: &Nsf) -> Vec<u8> {
+    let load_offset = (nsf.load_addr - PRG_ROM_START) as usize;
+
+    let mut prg_rom = if nsf.bankswitched() {
+        let padding = load_offset % KB_4;
+        let mut prg_rom = vec![0; padding];
+        prg_rom.extend_from_slice(&nsf.data);
+        prg_rom
+    } else {
+        let mut prg_rom = vec![0; KB_32];
+        let len = nsf.data.len().min(KB_32 - load_offset);
+        prg_rom[load_offset..load_offset + len].copy_from_slice(&nsf.data[..len]);
+        prg_rom
+    };
+
+    prg_rom.resize(prg_rom.len().div_ceil(KB_4).max(1) * KB_4, 0);
+    prg_rom
+}
+
+/// INIT is called with the song in A and the region in X, 0 for NTSC and 1
+/// for PAL. Dendy has PAL's frame rate, so tunes are told it's PAL.
+fn driver(nsf: &Nsf, song: u8, region: Region) -> (Vec<u8>, u16, u16, u16) {
+    let pal = (region != Region::Ntsc) as u8;
+    let reset_src = format!(
+        "SEI
+        CLD
+        LDX #$FF
+        TXS
+        LDA #$0F
+        STA $4015
+        LDA #$40
+        STA $4017
+        LDA #${:02X}
+        LDX #${:02X}
+        JSR ${:04X}
+        STA ${:04X}
+        CLI",
+        song, pal, nsf.init_addr, DRIVER_REG
+    );
+    let mut driver = assemble(&reset_src).unwrap();
+    let idle_addr = DRIVER_START + driver.len() as u16;
+    driver.extend(assemble(&format!("JMP ${:04X}", idle_addr)).unwrap());
+
+    let irq_src = format!(
+        "PHA
+        TXA
+        PHA
+        TYA
+        PHA
+        STA ${:04X}
+        JSR ${:04X}
+        PLA
+        TAY
+        PLA
+        TAX
+        PLA
+        RTI",
+        DRIVER_REG, nsf.play_addr
+    );
+    let irq_addr = DRIVER_START + driver.len() as u16;
+    driver.extend(assemble(&irq_src).unwrap());
+
+    let nmi_addr = DRIVER_START + driver.len() as u16;
+    driver.extend(assemble("RTI").unwrap());
+
+    (driver, DRIVER_START, nmi_addr, irq_addr)
+}
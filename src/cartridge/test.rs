@@ -0,0 +1,256 @@
+use super::*;
+use crate::rom::{ConsoleType, Timing, TRAINER_SIZE};
+use crate::{KB_16, KB_8};
+
+struct TestMapper {}
+
+impl Mapper for TestMapper {
+    fn read_reg(&mut self, addr: u16, cart: &mut CartData) -> Option<u8> {
+        match addr {
+            0x5000 => Some(0xA5),
+            _ => None,
+        }
+    }
+
+    fn write_reg(&mut self, addr: u16, data: u8, cart: &mut CartData) {
+        *cart.nt_conf = OneScreenUpper;
+        *cart.irq = true;
+    }
+}
+
+fn test_rom(mapper: u16, submapper: u8) -> Rom {
+    Rom {
+        prg_rom: vec![0; KB_16],
+        chr_rom: vec![0; KB_8],
+        trainer: None,
+        mapper,
+        submapper,
+        prg_ram_size: KB_8,
+        prg_nvram_size: 0,
+        chr_ram_size: 0,
+        chr_nvram_size: 0,
+        vert_mirrored: false,
+        four_screen: false,
+        battery: false,
+        nes_2: false,
+        timing: Timing::Ntsc,
+        console_type: ConsoleType::Nes,
+        misc_roms: 0,
+        default_expansion: 0,
+        board: None,
+    }
+}
+
+#[test]
+fn register_mapper_custom() {
+    register_mapper(200, Some(1), |_| Box::new(TestMapper {}));
+
+    let mut cart = Cartridge::init(&test_rom(200, 1)).unwrap();
+    assert_eq!(cart.cpu_read(0x5000), 0xA5);
+    assert!(!cart.irq());
+
+    cart.cpu_write(0x8000, 0x00);
+    assert!(cart.irq());
+    assert!(cart.nt_conf == OneScreenUpper);
+}
+
+#[test]
+fn register_mapper_submapper_fallback() {
+    register_mapper(201, None, |_| Box::new(TestMapper {}));
+
+    assert!(lookup_mapper(201, 3).is_some());
+    assert!(lookup_mapper(202, 0).is_none());
+}
+
+#[test]
+fn unsupported_mapper() {
+    assert_eq!(
+        Cartridge::init(&test_rom(203, 2)).err(),
+        Some(NesError::UnsupportedMapper {
+            mapper: 203,
+            submapper: 2
+        })
+    );
+}
+
+#[test]
+fn fds_needs_bios() {
+    assert_eq!(
+        Cartridge::init(&test_rom(MAPPER_FDS, 0)).err(),
+        Some(NesError::MissingBios)
+    );
+}
+
+#[test]
+fn save_data_round_trip() {
+    let mut cart = Cartridge::init(&test_rom(0, 0)).unwrap();
+    cart.cpu_write(0x6000, 0x12);
+    cart.cpu_write(0x7FFF, 0x34);
+
+    let save = cart.save_data();
+    assert_eq!(save.len(), KB_8);
+
+    let mut cart = Cartridge::init(&test_rom(0, 0)).unwrap();
+    cart.load_save_data(&save);
+    assert_eq!(cart.cpu_read(0x6000), 0x12);
+    assert_eq!(cart.cpu_read(0x7FFF), 0x34);
+}
+
+#[test]
+fn chr_ram_sizes() {
+    let mut rom = test_rom(0, 0);
+    rom.chr_rom.clear();
+    let mut cart = Cartridge::init(&rom).unwrap();
+    cart.ppu_write(0x1FFF, 0x5A);
+    assert_eq!(cart.ppu_read(0x1FFF), 0x5A);
+
+    rom.chr_ram_size = 64;
+    assert_eq!(
+        Cartridge::init(&rom).err(),
+        Some(NesError::BadSize {
+            region: "CHR RAM",
+            size: 64
+        })
+    );
+}
+
+fn banked_rom(mapper: u16, prg_banks: usize, prg_bank_size: usize) -> Rom {
+    let mut rom = test_rom(mapper, 0);
+    rom.prg_rom = (0..prg_banks)
+        .flat_map(|bank| vec![bank as u8; prg_bank_size])
+        .collect();
+    rom
+}
+
+#[test]
+fn mapper2_prg_pages() {
+    let mut cart = Cartridge::init(&banked_rom(2, 8, KB_16)).unwrap();
+    assert_eq!(cart.cpu_read(0x8000), 0);
+    assert_eq!(cart.cpu_read(0xFFFF), 7);
+
+    cart.cpu_write(0x8000, 3);
+    assert_eq!(cart.cpu_read(0x8000), 3);
+    assert_eq!(cart.cpu_read(0xBFFF), 3);
+    assert_eq!(cart.cpu_read(0xC000), 7);
+
+    cart.cpu_write(0x8000, 9);
+    assert_eq!(cart.cpu_read(0x8000), 1);
+}
+
+#[test]
+fn mapper4_prg_pages() {
+    let mut cart = Cartridge::init(&banked_rom(4, 16, KB_8)).unwrap();
+    cart.cpu_write(0x8000, 0x06);
+    cart.cpu_write(0x8001, 0x05);
+    cart.cpu_write(0x8000, 0x07);
+    cart.cpu_write(0x8001, 0x09);
+    assert_eq!(cart.cpu_read(0x8000), 5);
+    assert_eq!(cart.cpu_read(0xA000), 9);
+    assert_eq!(cart.cpu_read(0xC000), 14);
+    assert_eq!(cart.cpu_read(0xE000), 15);
+
+    cart.cpu_write(0x8000, 0x46);
+    assert_eq!(cart.cpu_read(0x8000), 14);
+    assert_eq!(cart.cpu_read(0xC000), 5);
+}
+
+#[test]
+fn trainer() {
+    let mut rom = test_rom(0, 0);
+    rom.prg_ram_size = 0;
+    rom.trainer = Some((0..TRAINER_SIZE).map(|n| n as u8).collect());
+
+    let mut cart = Cartridge::init(&rom).unwrap();
+    assert_eq!(cart.cpu_read(TRAINER_START), 0x00);
+    assert_eq!(cart.cpu_read(TRAINER_START + 0x1FF), 0xFF);
+    assert_eq!(cart.cpu_read(PRG_RAM_START), 0x00);
+}
+
+fn test_nsf(load_addr: u16, bank_init: [u8; 8]) -> Nsf {
+    Nsf {
+        data: (0..KB_8 as u32).map(|n| (n >> 12) as u8 + 1).collect(),
+        load_addr,
+        init_addr: 0x8000,
+        play_addr: 0x8000,
+        num_songs: 1,
+        start_song: 0,
+        title: String::new(),
+        artist: String::new(),
+        copyright: String::new(),
+        play_speed_ntsc: 0,
+        play_speed_pal: 0,
+        bank_init,
+        pal: false,
+        dual_region: false,
+        expansion: 0,
+    }
+}
+
+#[test]
+fn nsf_prg_layout() {
+    let prg_rom = nsf_prg_rom(&test_nsf(0x8100, [0; 8]));
+    assert_eq!(prg_rom.len(), KB_32);
+    assert_eq!(prg_rom[0xFF], 0);
+    assert_eq!(prg_rom[0x100], 1);
+    assert_eq!(prg_rom[0x2100], 0);
+
+    let prg_rom = nsf_prg_rom(&test_nsf(0x9100, [0, 1, 2, 0, 0, 0, 0, 0]));
+    assert_eq!(prg_rom.len(), KB_8 + KB_4);
+    assert_eq!(prg_rom[0xFF], 0);
+    assert_eq!(prg_rom[0x100], 1);
+    assert_eq!(prg_rom[0x1100], 2);
+}
+
+#[test]
+fn nsf_bankswitch() {
+    let nsf = test_nsf(0x8000, [1, 0, 0, 0, 0, 0, 0, 1]);
+    let mut cart = Cartridge::init_nsf(&nsf, 0, Region::Ntsc);
+    assert_eq!(cart.cpu_read(0x8000), 2);
+    assert_eq!(cart.cpu_read(0x9000), 1);
+    assert_eq!(cart.cpu_read(0xF000), 2);
+
+    cart.cpu_write(0x5FF9, 1);
+    assert_eq!(cart.cpu_read(0x9000), 2);
+}
+
+#[test]
+fn nsf_driver_region() {
+    let nsf = test_nsf(0x8000, [0; 8]);
+
+    for (region, x) in [(Region::Ntsc, 0), (Region::Pal, 1), (Region::Dendy, 1)] {
+        let mut cart = Cartridge::init_nsf(&nsf, 2, region);
+        let driver: Vec<u8> = (0x4100..0x4120).map(|addr| cart.cpu_read(addr)).collect();
+        let init = driver
+            .windows(4)
+            .position(|ins| ins[..3] == [0xA9, 2, 0xA2])
+            .unwrap();
+
+        assert_eq!(driver[init + 3], x);
+    }
+}
+
+#[test]
+fn nsf_play_period() {
+    let mut nsf = test_nsf(0x8000, [0; 8]);
+    for (region, play_speed, period) in [(Region::Ntsc, 0, 29780), (Region::Pal, 20000, 33252)] {
+        nsf.play_speed_pal = play_speed;
+        let mut cart = Cartridge::init_nsf(&nsf, 0, region);
+
+        // PLAY isn't called until INIT returns.
+        for _ in 0..period * 2 {
+            cart.tick();
+        }
+        assert!(!cart.irq());
+
+        cart.cpu_write(0x40FF, 0);
+        for _ in 0..period - 1 {
+            cart.tick();
+        }
+        assert!(!cart.irq());
+        cart.tick();
+        assert!(cart.irq());
+
+        cart.cpu_write(0x40FF, 0);
+        assert!(!cart.irq());
+    }
+}
@@ -28,6 +28,10 @@ impl Controller {
         }
     }
 
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.buttons_dyn = buttons;
+    }
+
     pub fn update(&mut self) {
         if self.strobe {
             self.buttons_latched = self.buttons_dyn;
@@ -18,12 +18,18 @@ pub struct Cpu {
     pub irq: bool,
     pub nmi: bool,
     nmi_prev: bool,
-    irq_level_detected: bool,
-    nmi_edge_detected: bool,
-    irq_rdy: bool,
     nmi_latch: bool,
+    int_poll: bool,
+    int_pending: bool,
+    hw_int: bool,
+    int_vec: u16,
+    opcode: u8,
+    addr: u16,
+    ptr: u8,
+    val: u8,
+    page_crossed: bool,
     cycles: u32,
-    ins_cycles: u32,
+    ins_cycle: u32,
 }
 
 pub trait CpuBus {
@@ -37,69 +43,46 @@ pub const VEC_IRQ: u16 = 0xFFFE;
 pub const STACK_BASE: u16 = 0x0100;
 
 const NUM_CYCLES_INT: u32 = 7;
-const NUM_CYCLES_RTI: u32 = 6;
-const NUM_CYCLES_IMM: u32 = 2;
-const NUM_CYCLES_IMP: u32 = 2;
-const NUM_CYCLES_ZP_R: u32 = 3;
-const NUM_CYCLES_ZPI_R: u32 = 4;
-const NUM_CYCLES_ABS_R: u32 = 4;
-const NUM_CYCLES_ABSI_R: u32 = 4;
-const NUM_CYCLES_INDX_R: u32 = 6;
-const NUM_CYCLES_INDY_R: u32 = 5;
-const NUM_CYCLES_ACC: u32 = 2;
-const NUM_CYCLES_ZP_RW: u32 = 5;
-const NUM_CYCLES_ZPX_RW: u32 = 6;
-const NUM_CYCLES_ABS_RW: u32 = 6;
-const NUM_CYCLES_ABSX_RW: u32 = 7;
-const NUM_CYCLES_ZP_W: u32 = 4;
-const NUM_CYCLES_ZPI_W: u32 = 4;
-const NUM_CYCLES_ABS_W: u32 = 4;
-const NUM_CYCLES_ABSI_W: u32 = 5;
-const NUM_CYCLES_INDX_W: u32 = 6;
-const NUM_CYCLES_INDY_W: u32 = 6;
-const NUM_CYCLES_BR: u32 = 2;
-const NUM_CYCLES_JMP_ABS: u32 = 3;
-const NUM_CYCLES_JMP_IND: u32 = 5;
-const NUM_CYCLES_JSR: u32 = 6;
-const NUM_CYCLES_RTS: u32 = 6;
-const NUM_CYCLES_PUSH: u32 = 3;
-const NUM_CYCLES_PULL: u32 = 4;
 
 type InsR = fn(&mut Cpu, val: u8);
 type InsRW = fn(&mut Cpu, val: u8) -> u8;
 type InsImp = fn(&mut Cpu);
 
 impl Cpu {
+    /// Runs one cycle, making exactly the bus access the 6502 makes on it,
+    /// dummy reads and writes included.
     pub fn tick(&mut self, bus: &mut impl CpuBus) {
-        if self.ins_cycles == 0 {
-            self.ins_cycles = if self.reset {
-                self.int(bus, VEC_RESET, false)
-            } else if self.nmi_latch {
-                self.nmi_latch = false;
-                self.int(bus, VEC_NMI, false)
-            } else if self.irq_rdy && !self.i {
-                self.int(bus, VEC_IRQ, false)
-            } else {
-                let opcode = bus.cpu_read(self.pc());
-                self.exec(bus, opcode)
-            }
-        }
+        let done = if self.ins_cycle == 0 {
+            self.fetch(bus);
+            false
+        } else {
+            self.exec(bus, self.opcode)
+        };
 
-        if self.nmi_edge_detected {
+        if self.nmi && !self.nmi_prev {
             self.nmi_latch = true;
         }
-        self.irq_rdy = self.irq_level_detected;
-        self.nmi_edge_detected = self.nmi && !self.nmi_prev;
-        self.irq_level_detected = self.irq;
         self.nmi_prev = self.nmi;
+
+        // Interrupts are polled on each instruction's penultimate cycle, so
+        // e.g. CLI only lets an IRQ in after the next instruction. Interrupt
+        // sequences themselves don't poll.
+        let poll = self.int_poll;
+        self.int_poll = self.nmi_latch || (self.irq && !self.i);
+
+        if done {
+            self.int_pending = poll && self.opcode != 0x00;
+            self.ins_cycle = 0;
+        } else {
+            self.ins_cycle += 1;
+        }
         self.cycles += 1;
-        self.ins_cycles -= 1;
     }
 
     pub fn step(&mut self, bus: &mut impl CpuBus) {
         while {
             self.tick(bus);
-            self.ins_cycles != 0
+            self.ins_cycle != 0
         } {}
     }
 
@@ -107,7 +90,25 @@ impl Cpu {
         self.cycles
     }
 
-    fn exec(&mut self, bus: &mut impl CpuBus, opcode: u8) -> u32 {
+    /// Whether the current instruction has finished all its cycles.
+    pub fn ins_complete(&self) -> bool {
+        self.ins_cycle == 0
+    }
+
+    /// Interrupts run as BRK, with the opcode fetch thrown away.
+    fn fetch(&mut self, bus: &mut impl CpuBus) {
+        self.hw_int = self.reset || self.int_pending;
+
+        if self.hw_int {
+            bus.cpu_read(self.pc);
+            self.opcode = 0x00;
+        } else {
+            self.opcode = bus.cpu_read(self.pc());
+        }
+    }
+
+    /// Runs cycle `ins_cycle` of an instruction, returning true on its last.
+    fn exec(&mut self, bus: &mut impl CpuBus, opcode: u8) -> bool {
         match opcode {
             0x69 => self.imm(bus, Cpu::adc),
             0x65 => self.zp_r(bus, Cpu::adc),
@@ -140,7 +141,7 @@ impl Cpu {
             0x70 => self.br(bus, self.v),
             0x24 => self.zp_r(bus, Cpu::bit),
             0x2C => self.abs_r(bus, Cpu::bit),
-            0x00 => self.int(bus, VEC_IRQ, true),
+            0x00 => self.int(bus),
             0x18 => self.imp(bus, Cpu::clc),
             0xD8 => self.imp(bus, Cpu::cld),
             0x58 => self.imp(bus, Cpu::cli),
@@ -264,275 +265,477 @@ impl Cpu {
         }
     }
 
-    fn int(&mut self, bus: &mut impl CpuBus, vec: u16, brk: bool) -> u32 {
-        if brk {
-            self.pc += 1;
+    fn int(&mut self, bus: &mut impl CpuBus) -> bool {
+        let brk = !self.hw_int;
+
+        match self.ins_cycle {
+            1 => {
+                bus.cpu_read(self.pc);
+                if brk {
+                    self.pc += 1;
+                }
+            }
+            2 => self.int_push(bus, self.pch()),
+            3 => self.int_push(bus, self.pcl()),
+            4 => {
+                // An NMI that comes in before the vector is read takes it
+                // over, even from BRK or an IRQ.
+                self.int_vec = if self.reset {
+                    VEC_RESET
+                } else if self.nmi_latch {
+                    self.nmi_latch = false;
+                    VEC_NMI
+                } else {
+                    VEC_IRQ
+                };
+
+                let flags = if brk {
+                    self.get_flags() | (1 << 4)
+                } else {
+                    self.get_flags()
+                };
+                self.int_push(bus, flags);
+            }
+            5 => {
+                self.val = bus.cpu_read(self.int_vec);
+                self.i = true;
+            }
+            _ => {
+                let vec_high = bus.cpu_read(self.int_vec + 1);
+                self.pc = u8_to_u16(self.val, vec_high);
+                self.reset = false;
+                return true;
+            }
         }
 
-        let (pcl, pch) = (self.pcl(), self.pch());
-        self.push(bus, pch);
-        self.push(bus, pcl);
+        false
+    }
 
-        let flags = if brk {
-            self.get_flags() | (1 << 4)
+    /// Reset goes through the motions of pushing, but reads instead.
+    fn int_push(&mut self, bus: &mut impl CpuBus, val: u8) {
+        if self.reset {
+            bus.cpu_read(stack(self.s));
+            self.s -= 1;
         } else {
-            self.get_flags()
-        };
-        self.push(bus, flags);
-
-        let vec_low = bus.cpu_read(vec);
-        let vec_high = bus.cpu_read(vec + 1);
-
-        self.pc = u8_to_u16(vec_low, vec_high);
-
-        self.i = true;
-
-        NUM_CYCLES_INT
+            self.push(bus, val);
+        }
     }
 
-    fn rti(&mut self, bus: &mut impl CpuBus) -> u32 {
-        let flags = self.pull(bus);
-        let addr_low = self.pull(bus);
-        let addr_high = self.pull(bus);
-        self.set_flags(flags);
-        self.pc = u8_to_u16(addr_low, addr_high);
+    fn rti(&mut self, bus: &mut impl CpuBus) -> bool {
+        match self.ins_cycle {
+            1 => self.dummy_read_pc(bus),
+            2 => self.dummy_read_stack(bus),
+            3 => {
+                let flags = self.pull(bus);
+                self.set_flags(flags);
+            }
+            4 => self.val = self.pull(bus),
+            _ => {
+                let addr_high = self.pull(bus);
+                self.pc = u8_to_u16(self.val, addr_high);
+                return true;
+            }
+        }
 
-        NUM_CYCLES_RTI
+        false
     }
 
-    fn imp(&mut self, bus: &mut impl CpuBus, ins: InsImp) -> u32 {
+    fn imp(&mut self, bus: &mut impl CpuBus, ins: InsImp) -> bool {
+        self.dummy_read_pc(bus);
         ins(self);
 
-        NUM_CYCLES_IMP
+        true
     }
 
-    fn imm(&mut self, bus: &mut impl CpuBus, ins: InsR) -> u32 {
+    fn imm(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
         let val = bus.cpu_read(self.pc());
         ins(self, val);
 
-        NUM_CYCLES_IMM
+        true
     }
 
-    fn zp_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let val = bus.cpu_read(addr as u16);
-        ins(self, val);
+    fn zp_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        match self.ins_cycle {
+            1 => self.zp_addr(bus),
+            _ => return self.read(bus, ins),
+        }
 
-        NUM_CYCLES_ZP_R
+        false
     }
 
-    fn zpi_r(&mut self, bus: &mut impl CpuBus, ins: InsR, idx: u8) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let val = bus.cpu_read((addr + idx) as u16);
-        ins(self, val);
+    fn zpi_r(&mut self, bus: &mut impl CpuBus, ins: InsR, idx: u8) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.zpi_addr(bus, idx),
+            _ => return self.read(bus, ins),
+        }
 
-        NUM_CYCLES_ZPI_R
+        false
     }
 
-    fn abs_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        let val = bus.cpu_read(u8_to_u16(addr_low, addr_high));
-        ins(self, val);
+    fn abs_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.abs_addr(bus),
+            _ => return self.read(bus, ins),
+        }
 
-        NUM_CYCLES_ABS_R
+        false
     }
 
-    fn absi_r(&mut self, bus: &mut impl CpuBus, ins: InsR, idx: u8) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        let val = bus.cpu_read(u8_to_u16(addr_low, addr_high) + (idx as u16));
-        ins(self, val);
-
-        if check_overflow(addr_low, idx) {
-            NUM_CYCLES_ABSI_R + 1
-        } else {
-            NUM_CYCLES_ABSI_R
+    fn absi_r(&mut self, bus: &mut impl CpuBus, ins: InsR, idx: u8) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.absi_addr(bus, idx),
+            3 => return self.indexed_read(bus, ins),
+            _ => return self.read(bus, ins),
         }
+
+        false
     }
 
-    fn indx_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let addr_low = bus.cpu_read((addr + self.x) as u16);
-        let addr_high = bus.cpu_read((addr + self.x + 1) as u16);
-        let val = bus.cpu_read(u8_to_u16(addr_low, addr_high));
-        ins(self, val);
+    fn indx_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        match self.ins_cycle {
+            1..=4 => self.indx_addr(bus),
+            _ => return self.read(bus, ins),
+        }
 
-        NUM_CYCLES_INDX_R
+        false
     }
 
-    fn indy_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let addr_low = bus.cpu_read(addr as u16);
-        let addr_high = bus.cpu_read((addr + 1) as u16);
-        let val = bus.cpu_read(u8_to_u16(addr_low, addr_high) + (self.y as u16));
-        ins(self, val);
-
-        if check_overflow(addr_low, self.y) {
-            NUM_CYCLES_INDY_R + 1
-        } else {
-            NUM_CYCLES_INDY_R
+    fn indy_r(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        match self.ins_cycle {
+            1..=3 => self.indy_addr(bus),
+            4 => return self.indexed_read(bus, ins),
+            _ => return self.read(bus, ins),
         }
+
+        false
     }
 
-    fn acc(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> u32 {
+    fn acc(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> bool {
+        self.dummy_read_pc(bus);
         self.a = ins(self, self.a);
 
-        NUM_CYCLES_ACC
+        true
     }
 
-    fn zp_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let val = bus.cpu_read(addr as u16);
-        bus.cpu_write(addr as u16, val);
-        bus.cpu_write(addr as u16, ins(self, val));
+    fn zp_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> bool {
+        match self.ins_cycle {
+            1 => self.zp_addr(bus),
+            cycle => return self.read_modify_write(bus, ins, cycle - 2),
+        }
 
-        NUM_CYCLES_ZP_RW
+        false
     }
 
-    fn zpx_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> u32 {
-        let addr = (bus.cpu_read(self.pc()) + self.x) as u16;
-        let val = bus.cpu_read(addr);
-        bus.cpu_write(addr, val);
-        bus.cpu_write(addr, ins(self, val));
+    fn zpx_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.zpi_addr(bus, self.x),
+            cycle => return self.read_modify_write(bus, ins, cycle - 3),
+        }
 
-        NUM_CYCLES_ZPX_RW
+        false
     }
 
-    fn abs_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        let addr = u8_to_u16(addr_low, addr_high);
-        let val = bus.cpu_read(addr);
-        bus.cpu_write(addr, val);
-        bus.cpu_write(addr, ins(self, val));
+    fn abs_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.abs_addr(bus),
+            cycle => return self.read_modify_write(bus, ins, cycle - 3),
+        }
 
-        NUM_CYCLES_ABS_RW
+        false
     }
 
-    fn absx_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        let addr = u8_to_u16(addr_low, addr_high) + (self.x as u16);
-        let val = bus.cpu_read(addr);
-        bus.cpu_write(addr, val);
-        bus.cpu_write(addr, ins(self, val));
+    fn absx_rw(&mut self, bus: &mut impl CpuBus, ins: InsRW) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.absi_addr(bus, self.x),
+            3 => self.indexed_dummy_read(bus),
+            cycle => return self.read_modify_write(bus, ins, cycle - 4),
+        }
 
-        NUM_CYCLES_ABSX_RW
+        false
     }
 
-    fn zp_w(&mut self, bus: &mut impl CpuBus, val: u8) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        bus.cpu_write(addr as u16, val);
+    fn zp_w(&mut self, bus: &mut impl CpuBus, val: u8) -> bool {
+        match self.ins_cycle {
+            1 => self.zp_addr(bus),
+            _ => return self.write(bus, val),
+        }
 
-        NUM_CYCLES_ZP_W
+        false
     }
 
-    fn zpi_w(&mut self, bus: &mut impl CpuBus, val: u8, idx: u8) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        bus.cpu_write((addr + idx) as u16, val);
+    fn zpi_w(&mut self, bus: &mut impl CpuBus, val: u8, idx: u8) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.zpi_addr(bus, idx),
+            _ => return self.write(bus, val),
+        }
 
-        NUM_CYCLES_ZPI_W
+        false
     }
 
-    fn abs_w(&mut self, bus: &mut impl CpuBus, val: u8) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        bus.cpu_write(u8_to_u16(addr_low, addr_high), val);
+    fn abs_w(&mut self, bus: &mut impl CpuBus, val: u8) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.abs_addr(bus),
+            _ => return self.write(bus, val),
+        }
 
-        NUM_CYCLES_ABS_W
+        false
     }
 
-    fn absi_w(&mut self, bus: &mut impl CpuBus, val: u8, idx: u8) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        bus.cpu_write(u8_to_u16(addr_low, addr_high) + (idx as u16), val);
+    fn absi_w(&mut self, bus: &mut impl CpuBus, val: u8, idx: u8) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.absi_addr(bus, idx),
+            3 => self.indexed_dummy_read(bus),
+            _ => return self.write(bus, val),
+        }
 
-        NUM_CYCLES_ABSI_W
+        false
     }
 
-    fn indx_w(&mut self, bus: &mut impl CpuBus, val: u8) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let addr_low = bus.cpu_read((addr + self.x) as u16);
-        let addr_high = bus.cpu_read((addr + self.x + 1) as u16);
-        bus.cpu_write(u8_to_u16(addr_low, addr_high), val);
+    fn indx_w(&mut self, bus: &mut impl CpuBus, val: u8) -> bool {
+        match self.ins_cycle {
+            1..=4 => self.indx_addr(bus),
+            _ => return self.write(bus, val),
+        }
 
-        NUM_CYCLES_INDX_W
+        false
     }
 
-    fn indy_w(&mut self, bus: &mut impl CpuBus, val: u8) -> u32 {
-        let addr = bus.cpu_read(self.pc());
-        let addr_low = bus.cpu_read(addr as u16);
-        let addr_high = bus.cpu_read((addr + 1) as u16);
-        bus.cpu_write(u8_to_u16(addr_low, addr_high) + (self.y as u16), val);
+    fn indy_w(&mut self, bus: &mut impl CpuBus, val: u8) -> bool {
+        match self.ins_cycle {
+            1..=3 => self.indy_addr(bus),
+            4 => self.indexed_dummy_read(bus),
+            _ => return self.write(bus, val),
+        }
 
-        NUM_CYCLES_INDY_W
+        false
     }
 
-    fn br(&mut self, bus: &mut impl CpuBus, cond: bool) -> u32 {
-        let offset = bus.cpu_read(self.pc());
+    fn br(&mut self, bus: &mut impl CpuBus, cond: bool) -> bool {
+        match self.ins_cycle {
+            1 => {
+                self.val = bus.cpu_read(self.pc());
+                !cond
+            }
+            2 => {
+                self.dummy_read_pc(bus);
+                self.addr = add_u16_i8(self.pc, self.val as i8);
+                self.pc = u8_to_u16(self.addr as u8, self.pch());
+                self.pc == self.addr
+            }
+            _ => {
+                self.dummy_read_pc(bus);
+                self.pc = self.addr;
+                true
+            }
+        }
+    }
 
-        if !cond {
-            NUM_CYCLES_BR
+    fn jmp_abs(&mut self, bus: &mut impl CpuBus) -> bool {
+        self.abs_addr(bus);
+
+        if self.ins_cycle == 2 {
+            self.pc = self.addr;
+            true
         } else {
-            let tmp_pc = u8_to_u16(self.pcl() + offset, self.pch());
-            self.pc = add_u16_i8(self.pc, offset as i8);
-            if self.pc == tmp_pc {
-                NUM_CYCLES_BR + 1
-            } else {
-                NUM_CYCLES_BR + 2
+            false
+        }
+    }
+
+    fn jmp_ind(&mut self, bus: &mut impl CpuBus) -> bool {
+        match self.ins_cycle {
+            1 | 2 => self.abs_addr(bus),
+            3 => self.val = bus.cpu_read(self.addr),
+            _ => {
+                let (ptr_low, ptr_high) = (self.addr as u8, (self.addr >> 8) as u8);
+                let addr_high = bus.cpu_read(u8_to_u16(ptr_low.wrapping_add(1), ptr_high));
+                self.pc = u8_to_u16(self.val, addr_high);
+                return true;
             }
         }
+
+        false
     }
 
-    fn jmp_abs(&mut self, bus: &mut impl CpuBus) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let addr_high = bus.cpu_read(self.pc());
-        self.pc = u8_to_u16(addr_low, addr_high);
+    fn jsr(&mut self, bus: &mut impl CpuBus) -> bool {
+        match self.ins_cycle {
+            1 => self.val = bus.cpu_read(self.pc()),
+            2 => self.dummy_read_stack(bus),
+            3 => self.push(bus, self.pch()),
+            4 => self.push(bus, self.pcl()),
+            _ => {
+                let addr_high = bus.cpu_read(self.pc);
+                self.pc = u8_to_u16(self.val, addr_high);
+                return true;
+            }
+        }
 
-        NUM_CYCLES_JMP_ABS
+        false
     }
 
-    fn jmp_ind(&mut self, bus: &mut impl CpuBus) -> u32 {
-        let ptr_low = bus.cpu_read(self.pc());
-        let ptr_high = bus.cpu_read(self.pc());
-        let addr_low = bus.cpu_read(u8_to_u16(ptr_low, ptr_high));
-        let addr_high = bus.cpu_read(u8_to_u16(ptr_low + 1, ptr_high));
-        self.pc = u8_to_u16(addr_low, addr_high);
+    fn rts(&mut self, bus: &mut impl CpuBus) -> bool {
+        match self.ins_cycle {
+            1 => self.dummy_read_pc(bus),
+            2 => self.dummy_read_stack(bus),
+            3 => self.val = self.pull(bus),
+            4 => {
+                let addr_high = self.pull(bus);
+                self.pc = u8_to_u16(self.val, addr_high);
+            }
+            _ => {
+                bus.cpu_read(self.pc());
+                return true;
+            }
+        }
 
-        NUM_CYCLES_JMP_IND
+        false
     }
 
-    fn jsr(&mut self, bus: &mut impl CpuBus) -> u32 {
-        let addr_low = bus.cpu_read(self.pc());
-        let (pcl, pch) = (self.pcl(), self.pch());
-        self.push(bus, pch);
-        self.push(bus, pcl);
-        let addr_high = bus.cpu_read(self.pc());
-        self.pc = u8_to_u16(addr_low, addr_high);
+    fn ph(&mut self, bus: &mut impl CpuBus, val: u8) -> bool {
+        match self.ins_cycle {
+            1 => self.dummy_read_pc(bus),
+            _ => {
+                self.push(bus, val);
+                return true;
+            }
+        }
 
-        NUM_CYCLES_JSR
+        false
     }
 
-    fn rts(&mut self, bus: &mut impl CpuBus) -> u32 {
-        let addr_low = self.pull(bus);
-        let addr_high = self.pull(bus);
-        self.pc = u8_to_u16(addr_low, addr_high) + 1;
+    fn pl(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        match self.ins_cycle {
+            1 => self.dummy_read_pc(bus),
+            2 => self.dummy_read_stack(bus),
+            _ => {
+                let val = self.pull(bus);
+                ins(self, val);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn zp_addr(&mut self, bus: &mut impl CpuBus) {
+        self.addr = bus.cpu_read(self.pc()) as u16;
+    }
 
-        NUM_CYCLES_RTS
+    /// Two cycles, the second reading the unindexed address.
+    fn zpi_addr(&mut self, bus: &mut impl CpuBus, idx: u8) {
+        if self.ins_cycle == 1 {
+            self.ptr = bus.cpu_read(self.pc());
+        } else {
+            bus.cpu_read(self.ptr as u16);
+            self.addr = self.ptr.wrapping_add(idx) as u16;
+        }
     }
 
-    fn ph(&mut self, bus: &mut impl CpuBus, val: u8) -> u32 {
-        self.push(bus, val);
+    fn abs_addr(&mut self, bus: &mut impl CpuBus) {
+        if self.ins_cycle == 1 {
+            self.addr = bus.cpu_read(self.pc()) as u16;
+        } else {
+            let addr_high = bus.cpu_read(self.pc());
+            self.addr = u8_to_u16(self.addr as u8, addr_high);
+        }
+    }
 
-        NUM_CYCLES_PUSH
+    /// Leaves `addr` without the carry into its high byte, see
+    /// `indexed_read`.
+    fn absi_addr(&mut self, bus: &mut impl CpuBus, idx: u8) {
+        if self.ins_cycle == 1 {
+            self.addr = bus.cpu_read(self.pc()) as u16;
+        } else {
+            let (addr_low, addr_high) = (self.addr as u8, bus.cpu_read(self.pc()));
+            self.page_crossed = check_overflow(addr_low, idx);
+            self.addr = u8_to_u16(addr_low.wrapping_add(idx), addr_high);
+        }
     }
 
-    fn pl(&mut self, bus: &mut impl CpuBus, ins: InsR) -> u32 {
-        let val = self.pull(bus);
+    fn indx_addr(&mut self, bus: &mut impl CpuBus) {
+        match self.ins_cycle {
+            1 => self.ptr = bus.cpu_read(self.pc()),
+            2 => {
+                bus.cpu_read(self.ptr as u16);
+                self.ptr = self.ptr.wrapping_add(self.x);
+            }
+            3 => self.addr = bus.cpu_read(self.ptr as u16) as u16,
+            _ => {
+                let addr_high = bus.cpu_read(self.ptr.wrapping_add(1) as u16);
+                self.addr = u8_to_u16(self.addr as u8, addr_high);
+            }
+        }
+    }
+
+    /// Leaves `addr` without the carry into its high byte, like
+    /// `absi_addr`.
+    fn indy_addr(&mut self, bus: &mut impl CpuBus) {
+        match self.ins_cycle {
+            1 => self.ptr = bus.cpu_read(self.pc()),
+            2 => self.addr = bus.cpu_read(self.ptr as u16) as u16,
+            _ => {
+                let addr_low = self.addr as u8;
+                let addr_high = bus.cpu_read(self.ptr.wrapping_add(1) as u16);
+                self.page_crossed = check_overflow(addr_low, self.y);
+                self.addr = u8_to_u16(addr_low.wrapping_add(self.y), addr_high);
+            }
+        }
+    }
+
+    fn read(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        let val = bus.cpu_read(self.addr);
         ins(self, val);
 
-        NUM_CYCLES_PULL
+        true
+    }
+
+    /// Indexed reads go ahead before the high byte of the address is fixed
+    /// up, and only take another cycle to read the right address if a page
+    /// was crossed.
+    fn indexed_read(&mut self, bus: &mut impl CpuBus, ins: InsR) -> bool {
+        if self.page_crossed {
+            self.indexed_dummy_read(bus);
+            false
+        } else {
+            self.read(bus, ins)
+        }
+    }
+
+    /// Writes and read-modify-writes always spend a cycle reading the
+    /// unfixed address.
+    fn indexed_dummy_read(&mut self, bus: &mut impl CpuBus) {
+        bus.cpu_read(self.addr);
+        if self.page_crossed {
+            self.addr = self.addr.wrapping_add(0x100);
+        }
+    }
+
+    /// The read, then the unmodified value written back while the
+    /// instruction runs, then the result written.
+    fn read_modify_write(&mut self, bus: &mut impl CpuBus, ins: InsRW, cycle: u32) -> bool {
+        match cycle {
+            0 => self.val = bus.cpu_read(self.addr),
+            1 => {
+                bus.cpu_write(self.addr, self.val);
+                self.val = ins(self, self.val);
+            }
+            _ => return self.write(bus, self.val),
+        }
+
+        false
+    }
+
+    fn write(&mut self, bus: &mut impl CpuBus, val: u8) -> bool {
+        bus.cpu_write(self.addr, val);
+
+        true
+    }
+
+    fn dummy_read_pc(&mut self, bus: &mut impl CpuBus) {
+        bus.cpu_read(self.pc);
+    }
+
+    fn dummy_read_stack(&mut self, bus: &mut impl CpuBus) {
+        bus.cpu_read(stack(self.s));
     }
 
     fn pc(&mut self) -> u16 {
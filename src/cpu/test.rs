@@ -1,24 +1,37 @@
 use super::*;
-use crate::assemble::assemble;
+use crate::assemble::{assemble, disassemble};
+use serde_json::Value;
+use std::{env, fs, path::Path};
 
 const PRG_ADDR: u16 = 0x8000;
+/// Directory of the NES variant of the SingleStepTests/ProcessorTests 6502
+/// tests (`nes6502/v1`), one `<opcode>.json` file per opcode.
+const PROCESSOR_TESTS_DIR_VAR: &str = "PROCESSOR_TESTS_DIR";
 
 struct TestCpuBus {
     mem: [u8; 0x10000],
+    /// Every access in order, as address, data and whether it's a write.
+    accesses: Vec<(u16, u8, bool)>,
 }
 
 impl Default for TestCpuBus {
     fn default() -> Self {
-        Self { mem: [0; 0x10000] }
+        Self {
+            mem: [0; 0x10000],
+            accesses: vec![],
+        }
     }
 }
 
 impl CpuBus for TestCpuBus {
     fn cpu_read(&mut self, addr: u16) -> u8 {
-        self.mem[addr as usize]
+        let data = self.mem[addr as usize];
+        self.accesses.push((addr, data, false));
+        data
     }
 
     fn cpu_write(&mut self, addr: u16, data: u8) -> () {
+        self.accesses.push((addr, data, true));
         self.mem[addr as usize] = data
     }
 }
@@ -952,6 +965,7 @@ fn brk() {
 fn irq() {
     let (mut cpu, mut bus) = init(
         "CLI
+        NOP
         LDA #$AA",
         PRG_ADDR,
     );
@@ -968,6 +982,9 @@ fn irq() {
     cpu.irq = true;
     cpu.step(&mut bus);
     assert_eq!(cpu.i, false);
+    // CLI only takes effect after the next instruction.
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x8002);
     cpu.step(&mut bus);
     assert_eq!(cpu.i, true);
     assert_eq!(cpu.pc, 0x1111);
@@ -977,7 +994,7 @@ fn irq() {
     cpu.step(&mut bus);
     assert_eq!(cpu.d, false);
     assert_eq!(cpu.i, false);
-    assert_eq!(cpu.pc, 0x8001);
+    assert_eq!(cpu.pc, 0x8002);
     cpu.step(&mut bus);
     assert_eq!(cpu.a, 0xAA);
 }
@@ -1018,3 +1035,240 @@ fn nmi() {
     cpu.step(&mut bus);
     assert_eq!(cpu.a, 0xFF);
 }
+
+#[test]
+fn rmw_dummy_write() {
+    let (mut cpu, mut bus) = init("INC $10", PRG_ADDR);
+    bus.cpu_write(0x0010, 0x05);
+    bus.accesses.clear();
+
+    cpu.step(&mut bus);
+    assert_eq!(
+        bus.accesses,
+        [
+            (0x8000, 0xE6, false),
+            (0x8001, 0x10, false),
+            (0x0010, 0x05, false),
+            (0x0010, 0x05, true),
+            (0x0010, 0x06, true)
+        ]
+    );
+}
+
+#[test]
+fn indexed_dummy_reads() {
+    let (mut cpu, mut bus) = init(
+        "LDX #$FF
+        LDA $12F0,X
+        STA $1200,X",
+        PRG_ADDR,
+    );
+
+    bus.mem[0x12EF] = 0x11;
+    bus.mem[0x13EF] = 0x22;
+
+    cpu.step(&mut bus);
+    bus.accesses.clear();
+    cpu.step(&mut bus);
+    assert_eq!(
+        bus.accesses,
+        [
+            (0x8002, 0xBD, false),
+            (0x8003, 0xF0, false),
+            (0x8004, 0x12, false),
+            (0x12EF, 0x11, false),
+            (0x13EF, 0x22, false)
+        ]
+    );
+
+    bus.accesses.clear();
+    cpu.step(&mut bus);
+    assert_eq!(
+        bus.accesses,
+        [
+            (0x8005, 0x9D, false),
+            (0x8006, 0x00, false),
+            (0x8007, 0x12, false),
+            (0x12FF, 0x00, false),
+            (0x12FF, 0x22, true)
+        ]
+    );
+}
+
+#[test]
+fn nmi_hijacks_brk() {
+    let (mut cpu, mut bus) = init("BRK", PRG_ADDR);
+    bus.cpu_write_16(VEC_IRQ, 0x1111);
+    bus.cpu_write_16(VEC_NMI, 0x2222);
+
+    cpu.tick(&mut bus);
+    cpu.tick(&mut bus);
+    cpu.nmi = true;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x2222);
+    assert_eq!(bus.cpu_read(0x01FB) & (1 << 4), 1 << 4);
+}
+
+/// Reads a number from a ProcessorTests case, all of which fit in 16 bits.
+fn num(val: &Value) -> i64 {
+    val.as_i64()
+        .unwrap_or_else(|| panic!("Not a number: {}", val))
+}
+
+fn arr(val: &Value) -> &[Value] {
+    val.as_array()
+        .unwrap_or_else(|| panic!("Not an array: {}", val))
+}
+
+fn set_test_state(cpu: &mut Cpu, bus: &mut TestCpuBus, state: &Value) {
+    cpu.pc = num(&state["pc"]) as u16;
+    cpu.s = num(&state["s"]) as u8;
+    cpu.a = num(&state["a"]) as u8;
+    cpu.x = num(&state["x"]) as u8;
+    cpu.y = num(&state["y"]) as u8;
+    cpu.set_flags(num(&state["p"]) as u8);
+
+    for entry in arr(&state["ram"]) {
+        bus.mem[num(&entry[0]) as usize] = num(&entry[1]) as u8;
+    }
+}
+
+/// Runs one ProcessorTests case, describing the first difference found.
+fn run_processor_test(case: &Value) -> Result<(), String> {
+    let mut cpu = Cpu::default();
+    let mut bus = TestCpuBus::default();
+    set_test_state(&mut cpu, &mut bus, &case["initial"]);
+
+    cpu.step(&mut bus);
+
+    let expected = &case["final"];
+    let regs = [
+        ("pc", cpu.pc as i64),
+        ("s", cpu.s as i64),
+        ("a", cpu.a as i64),
+        ("x", cpu.x as i64),
+        ("y", cpu.y as i64),
+        ("p", (cpu.get_flags() | 0x30) as i64),
+    ];
+
+    for (name, val) in regs {
+        let mut expected_val = num(&expected[name]);
+        if name == "p" {
+            expected_val |= 0x30;
+        }
+        if val != expected_val {
+            return Err(format!(
+                "{} is {:X}, expected {:X}",
+                name, val, expected_val
+            ));
+        }
+    }
+
+    for entry in arr(&expected["ram"]) {
+        let addr = num(&entry[0]) as usize;
+        let expected_val = num(&entry[1]) as u8;
+        if bus.mem[addr] != expected_val {
+            return Err(format!(
+                "${:04X} is {:02X}, expected {:02X}",
+                addr, bus.mem[addr], expected_val
+            ));
+        }
+    }
+
+    for (cycle, entry) in arr(&case["cycles"]).iter().enumerate() {
+        let expected_access = (
+            num(&entry[0]) as u16,
+            num(&entry[1]) as u8,
+            entry[2] == "write",
+        );
+
+        if bus.accesses.get(cycle) != Some(&expected_access) {
+            let describe = |(addr, data, write): (u16, u8, bool)| {
+                let kind = if write { "write" } else { "read" };
+                format!("{} {:02X} at ${:04X}", kind, data, addr)
+            };
+
+            return Err(format!(
+                "Cycle {} should {}, found {}",
+                cycle,
+                describe(expected_access),
+                bus.accesses
+                    .get(cycle)
+                    .map_or(String::from("nothing"), |&access| describe(access))
+            ));
+        }
+    }
+
+    if bus.accesses.len() != arr(&case["cycles"]).len() {
+        return Err(format!("Made {} bus accesses", bus.accesses.len()));
+    }
+
+    if cpu.cycles() as usize != arr(&case["cycles"]).len() {
+        return Err(format!("Took {} cycles", cpu.cycles()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn processor_test_case() {
+    let case_src = r#"{
+            "name": "7d 10 ff",
+            "initial": {"pc": 512, "s": 253, "a": 1, "x": 255, "y": 0, "p": 36,
+                "ram": [[512, 125], [513, 16], [514, 255], [15, 99]]},
+            "final": {"pc": 515, "s": 253, "a": 100, "x": 255, "y": 0, "p": 36,
+                "ram": [[15, 99]]},
+            "cycles": [[512, 125, "read"], [513, 16, "read"], [514, 255, "read"],
+                [65295, 0, "read"], [15, 99, "read"]]
+        }"#;
+
+    let case: Value = serde_json::from_str(case_src).unwrap();
+    assert_eq!(run_processor_test(&case), Ok(()));
+
+    let case: Value =
+        serde_json::from_str(&case_src.replace("[15, 99, \"read\"]", "[15, 98, \"read\"]"))
+            .unwrap();
+    assert_eq!(
+        run_processor_test(&case),
+        Err(String::from(
+            "Cycle 4 should read 62 at $000F, found read 63 at $000F"
+        ))
+    );
+
+    let case: Value =
+        serde_json::from_str(&case_src.replace("[15, 99, \"read\"]", "[15, 99, \"write\"]"))
+            .unwrap();
+    assert!(run_processor_test(&case).is_err());
+}
+
+/// Runs the ProcessorTests cases for every documented opcode, if they've
+/// been downloaded to `$PROCESSOR_TESTS_DIR`.
+#[test]
+fn processor_tests() {
+    let Some(dir) = env::var_os(PROCESSOR_TESTS_DIR_VAR) else {
+        eprintln!("{} not set, skipping", PROCESSOR_TESTS_DIR_VAR);
+        return;
+    };
+    let mut failures = vec![];
+
+    for opcode in 0..=0xFF_u8 {
+        let path = Path::new(&dir).join(format!("{:02x}.json", opcode));
+        if disassemble(&[opcode, 0, 0]).is_err() || !path.is_file() {
+            continue;
+        }
+
+        let cases: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        for case in arr(&cases) {
+            if let Err(err) = run_processor_test(case) {
+                failures.push(format!("{}: {}", case["name"].as_str().unwrap(), err));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} failures, first: {}",
+        failures.len(),
+        failures.first().unwrap()
+    );
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NesError {
+    /// The file ended before all the data its header describes.
+    TruncatedFile {
+        expected: usize,
+        actual: usize,
+    },
+    BadMagic,
+    UnsupportedMapper {
+        mapper: u16,
+        submapper: u8,
+    },
+    /// A ROM or RAM region has a size the emulator can't map.
+    BadSize {
+        region: &'static str,
+        size: usize,
+    },
+    /// A UNIF board name with no known mapper number.
+    UnknownBoard(String),
+    /// An NSF or NSFe file that can't be played, e.g. it has no songs.
+    BadNsf(String),
+    /// The board needs a BIOS image (e.g. the Famicom Disk System) and none was given.
+    MissingBios,
+}
+
+impl fmt::Display for NesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NesError::TruncatedFile { expected, actual } => write!(
+                f,
+                "File is truncated: expected {} bytes, found {}",
+                expected, actual
+            ),
+            NesError::BadMagic => write!(f, "File is not an iNES, NES 2.0, UNIF or NSF file"),
+            NesError::UnsupportedMapper { mapper, submapper } => {
+                write!(f, "Invalid or unsupported mapper: {}.{}", mapper, submapper)
+            }
+            NesError::BadSize { region, size } => write!(f, "Bad {} size: {}", region, size),
+            NesError::UnknownBoard(board) => write!(f, "Unknown UNIF board: {}", board),
+            NesError::BadNsf(reason) => write!(f, "Bad NSF: {}", reason),
+            NesError::MissingBios => write!(f, "A BIOS image is required but was not provided"),
+        }
+    }
+}
+
+impl std::error::Error for NesError {}
@@ -1,20 +1,59 @@
+#[cfg(test)]
+mod test;
+
 #[path = "assemble/assemble.rs"]
 pub mod assemble;
 
 #[path = "utils/bitfield.rs"]
 pub mod bitfield;
 
+#[path = "capture/capture.rs"]
+pub mod capture;
+
+#[path = "utils/hash.rs"]
+pub mod hash;
+
 #[path = "rom/rom.rs"]
 pub mod rom;
 
+#[path = "error/error.rs"]
+pub mod error;
+
+#[path = "movie/movie.rs"]
+pub mod movie;
+
+#[path = "nsf/nsf.rs"]
+pub mod nsf;
+
+#[path = "ntsc/ntsc.rs"]
+pub mod ntsc;
+
+#[path = "palette/palette.rs"]
+pub mod palette;
+
+#[path = "png/png.rs"]
+pub mod png;
+
+#[path = "patch/patch.rs"]
+pub mod patch;
+
+#[path = "region/region.rs"]
+pub mod region;
+
+#[path = "test_rom/test_rom.rs"]
+pub mod test_rom;
+
 #[path = "cpu/cpu.rs"]
 mod cpu;
 
 #[path = "ppu/ppu.rs"]
 mod ppu;
 
+#[path = "video/video.rs"]
+pub mod video;
+
 #[path = "cartridge/cartridge.rs"]
-mod cartridge;
+pub mod cartridge;
 
 #[path = "controller/controller.rs"]
 mod controller;
@@ -23,12 +62,18 @@ use cartridge::Cartridge;
 pub use controller::Button;
 use controller::Controller;
 use cpu::{Cpu, CpuBus};
+pub use error::NesError;
+use nsf::Nsf;
+use palette::Palette;
 use ppu::{Ppu, PpuBus, OAM_ADDR, OAM_DATA};
+pub use region::Region;
 use rom::Rom;
+use video::VideoSink;
 
 pub const DISPLAY_WIDTH: u32 = 256;
 pub const DISPLAY_HEIGHT: u32 = 240;
-pub const FRAME_SIZE_BYTES: usize = (DISPLAY_WIDTH * DISPLAY_HEIGHT * 3) as usize;
+pub const FRAME_SIZE_PIXELS: usize = (DISPLAY_WIDTH * DISPLAY_HEIGHT) as usize;
+pub const FRAME_SIZE_BYTES: usize = FRAME_SIZE_PIXELS * 3;
 pub const KB_1: usize = 1024;
 pub const KB_2: usize = KB_1 * 2;
 pub const KB_4: usize = KB_2 * 2;
@@ -57,7 +102,11 @@ pub struct Nes {
     dma_addr: u16,
     dma_data: u8,
     dma_write_toggle: bool,
+    dma_halted: bool,
     cpu_bus_val: u8,
+    region: Region,
+    master_clock: u64,
+    cpu_clock: u64,
 }
 
 macro_rules! cpu_bus {
@@ -83,18 +132,42 @@ macro_rules! ppu_bus {
 }
 
 impl Nes {
-    pub fn init(rom: &Rom) -> Self {
+    pub fn init(rom: &Rom) -> Result<Self, NesError> {
+        Self::init_with_region(rom, Region::from_timing(rom.timing))
+    }
+
+    /// Like `init`, but ignores the region given by the ROM's header.
+    pub fn init_with_region(rom: &Rom, region: Region) -> Result<Self, NesError> {
+        Ok(Self::init_with_cartridge(
+            Cartridge::init(rom)?,
+            0xFF,
+            region,
+        ))
+    }
+
+    /// Builds a console that plays `song` (zero-based) of the given tune.
+    /// Switching tracks is done by initializing a new console. See
+    /// `Nsf::region` for the region the tune asks for.
+    pub fn init_nsf(nsf: &Nsf, song: u8, region: Region) -> Self {
+        Self::init_with_cartridge(Cartridge::init_nsf(nsf, song, region), 0x00, region)
+    }
+
+    fn init_with_cartridge(cartridge: Cartridge, ram_fill: u8, region: Region) -> Self {
         let mut nes = Self {
             cpu: Cpu::default(),
-            ppu: Ppu::default(),
-            ram: [0xff; RAM_SIZE],
-            cartridge: Cartridge::init(rom),
+            ppu: Ppu::init(region),
+            ram: [ram_fill; RAM_SIZE],
+            cartridge,
             controller: Controller::default(),
             dma_flag: false,
             dma_addr: 0,
             dma_data: 0,
             dma_write_toggle: false,
+            dma_halted: false,
             cpu_bus_val: 0,
+            region,
+            master_clock: 0,
+            cpu_clock: 0,
         };
 
         nes.cpu.reset = true;
@@ -105,35 +178,105 @@ impl Nes {
     }
 
     pub fn frame(&mut self, frame: &mut [u8; FRAME_SIZE_BYTES]) {
-        for _ in 0..ppu::CYCLES_PER_FRAME {
-            self.tick(frame);
+        self.frame_to(frame);
+    }
+
+    /// Runs a frame, outputting each pixel's 9-bit palette index and
+    /// emphasis bits instead of RGB. See `palette::indices_to_rgb`.
+    pub fn frame_indices(&mut self, frame: &mut [u16; FRAME_SIZE_PIXELS]) {
+        self.frame_to(frame);
+    }
+
+    /// Runs a frame, drawing into any `VideoSink`, e.g. a
+    /// `video::PixelBuffer` in the frontend's pixel format.
+    pub fn frame_to(&mut self, sink: &mut impl VideoSink) {
+        let frame = self.ppu.frames();
+
+        while self.ppu.frames() == frame {
+            self.tick(sink);
         }
     }
 
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.ppu.set_palette(palette);
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn save_data(&self) -> Vec<u8> {
+        self.cartridge.save_data()
+    }
+
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        self.cartridge.load_save_data(data);
+    }
+
+    /// Presses the reset button, which resets the CPU at the start of its
+    /// next instruction and clears the PPU's control registers.
+    pub fn reset(&mut self) {
+        self.cpu.reset = true;
+        self.ppu.reset();
+    }
+
+    /// Reads from cartridge space, e.g. to check a test ROM's results in PRG
+    /// RAM.
+    pub fn cart_read(&mut self, addr: u16) -> u8 {
+        self.cartridge.cpu_read(addr)
+    }
+
     pub fn set_button_state(&mut self, button: Button, pressed: bool) {
         self.controller.set_button_state(button, pressed);
     }
 
-    fn tick(&mut self, frame: &mut [u8; FRAME_SIZE_BYTES]) {
+    /// Sets every button at once from a byte in the same bit order as
+    /// `Button`, e.g. a `movie::MovieFrame`.
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.controller.set_buttons(buttons);
+    }
+
+    /// Advances the master clock by one PPU cycle, and steps the CPU
+    /// whenever the master clock passes its next cycle.
+    fn tick(&mut self, frame: &mut impl VideoSink) {
+        self.master_clock += self.region.ppu_divider();
         self.ppu.tick(ppu_bus!(self.cartridge), frame);
 
         self.cpu.nmi = self.ppu.nmi();
         self.cpu.irq = self.cartridge.irq();
 
-        if self.ppu.cycles() % 3 == 0 {
-            if !self.dma_flag {
-                self.cpu.tick(cpu_bus!(self));
-                self.cartridge.tick();
-            } else {
+        if self.master_clock >= self.cpu_clock + self.region.cpu_divider() {
+            self.cpu_clock += self.region.cpu_divider();
+
+            // DMA halts the CPU on its next read, which is always the
+            // opcode fetch after the write to $4014.
+            if self.dma_flag && self.cpu.ins_complete() {
                 self.dma_tick();
+            } else {
+                self.cpu.tick(cpu_bus!(self));
             }
+            self.cartridge.tick();
 
             self.controller.update();
         }
     }
 
+    /// OAM DMA takes a cycle to halt the CPU, then reads on even cycles and
+    /// writes to OAMDATA on odd ones, waiting a cycle to line up if needed,
+    /// for 513 or 514 cycles in total. Reads go through the CPU bus, so
+    /// copying from a register page reads the registers. There's no APU yet,
+    /// so no DMC DMA to interleave with.
     fn dma_tick(&mut self) {
+        if !self.dma_halted {
+            self.dma_halted = true;
+            return;
+        }
+
         if !self.dma_write_toggle {
+            if self.cpu_cycle() % 2 == 1 {
+                return;
+            }
+
             let dma_addr = self.dma_addr;
             self.dma_data = cpu_bus!(self).cpu_read(dma_addr);
             self.dma_addr += 1;
@@ -146,11 +289,16 @@ impl Nes {
 
             if self.dma_addr & 0x00FF == 0 {
                 self.dma_flag = false;
+                self.dma_halted = false;
             }
         }
 
         self.dma_write_toggle = !self.dma_write_toggle;
     }
+
+    fn cpu_cycle(&self) -> u64 {
+        self.cpu_clock / self.region.cpu_divider()
+    }
 }
 
 struct NesCpuBus<'a> {
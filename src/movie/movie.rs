@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod test;
+
+/// Button characters of an FM2 gamepad field, from bit 7 down to bit 0 of
+/// the controller's button byte.
+const FM2_BUTTONS: &[u8] = b"RLDUTSBA";
+const FM2_RESET: u32 = 0x1;
+const FM2_POWER: u32 = 0x2;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct MovieFrame {
+    /// Pressed buttons on controller 1, in the same bit order as `Button`.
+    pub buttons: u8,
+    pub reset: bool,
+}
+
+/// Parses an FCEUX text movie (`.fm2`) into its inputs for each frame,
+/// starting from power on. Only controller 1 is used.
+pub fn fm2_parse(text: &str) -> Result<Vec<MovieFrame>, String> {
+    let mut frames = vec![];
+
+    for (num, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+
+        if !line.starts_with('|') {
+            if line == "binary 1" {
+                return Err(String::from("Binary FM2 movies aren't supported."));
+            }
+            continue;
+        }
+
+        let mut fields = line.split('|').skip(1);
+        let commands = fields.next().unwrap_or("");
+        let commands: u32 = match commands {
+            "" => 0,
+            _ => commands
+                .parse()
+                .map_err(|_| format!("Bad commands on line {}: {}", num + 1, commands))?,
+        };
+
+        if commands & FM2_POWER != 0 {
+            return Err(format!(
+                "Power cycling on line {} isn't supported.",
+                num + 1
+            ));
+        }
+
+        let port = fields.next().unwrap_or("").as_bytes();
+        if !port.is_empty() && port.len() != FM2_BUTTONS.len() {
+            return Err(format!("Bad gamepad input on line {}.", num + 1));
+        }
+
+        let buttons = port
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| c != b' ' && c != b'.')
+            .fold(0, |buttons, (idx, _)| buttons | (0x80 >> idx));
+
+        frames.push(MovieFrame {
+            buttons,
+            reset: commands & FM2_RESET != 0,
+        });
+    }
+
+    Ok(frames)
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn fm2() {
+    let frames = fm2_parse(
+        "version 3
+emuVersion 22020
+romFilename game
+|0|........|||
+|0|R......A|||
+|1|...UT...|||
+|0|        |||
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+        frames,
+        [
+            MovieFrame::default(),
+            MovieFrame {
+                buttons: 0x81,
+                reset: false
+            },
+            MovieFrame {
+                buttons: 0x18,
+                reset: true
+            },
+            MovieFrame::default(),
+        ]
+    );
+}
+
+#[test]
+fn fm2_errors() {
+    assert!(fm2_parse("binary 1\n").is_err());
+    assert!(fm2_parse("|2|........|||\n").is_err());
+    assert!(fm2_parse("|0|...|||\n").is_err());
+    assert!(fm2_parse("|x|........|||\n").is_err());
+}
@@ -0,0 +1,282 @@
+#[cfg(test)]
+mod test;
+
+use crate::error::NesError;
+use crate::region::Region;
+
+const NSF_MAGIC: &[u8] = b"NESM\x1A";
+const NSFE_MAGIC: &[u8] = b"NSFE";
+const NSF_HDR_SIZE: usize = 0x80;
+const NSF_STR_SIZE: usize = 32;
+const NSFE_CHUNK_HDR_SIZE: usize = 8;
+const NSFE_INFO_MIN_SIZE: usize = 8;
+const NUM_BANKS: usize = 8;
+const LOAD_ADDR_MIN: u16 = 0x8000;
+const DEFAULT_PLAY_SPEED_NTSC: u16 = 16639;
+const DEFAULT_PLAY_SPEED_PAL: u16 = 19997;
+
+pub const EXP_VRC6: u8 = 0x01;
+pub const EXP_VRC7: u8 = 0x02;
+pub const EXP_FDS: u8 = 0x04;
+pub const EXP_MMC5: u8 = 0x08;
+pub const EXP_N163: u8 = 0x10;
+pub const EXP_S5B: u8 = 0x20;
+
+pub struct Nsf {
+    pub data: Vec<u8>,
+    pub load_addr: u16,
+    pub init_addr: u16,
+    pub play_addr: u16,
+    pub num_songs: u8,
+    /// Zero-based, i.e. the value passed to INIT in the accumulator.
+    pub start_song: u8,
+    pub title: String,
+    pub artist: String,
+    pub copyright: String,
+    /// Microseconds between calls to PLAY.
+    pub play_speed_ntsc: u16,
+    pub play_speed_pal: u16,
+    /// All zero if the tune is not bankswitched.
+    pub bank_init: [u8; NUM_BANKS],
+    pub pal: bool,
+    pub dual_region: bool,
+    /// Bitmask of `EXP_*` expansion audio chips.
+    pub expansion: u8,
+}
+
+impl Nsf {
+    pub fn bankswitched(&self) -> bool {
+        self.bank_init.iter().any(|&bank| bank != 0)
+    }
+
+    /// Dual-region tunes run as NTSC.
+    pub fn region(&self) -> Region {
+        if self.pal && !self.dual_region {
+            Region::Pal
+        } else {
+            Region::Ntsc
+        }
+    }
+}
+
+pub fn is_nsf(data: &[u8]) -> bool {
+    data.starts_with(NSF_MAGIC) || data.starts_with(NSFE_MAGIC)
+}
+
+pub fn nsf_parse(data: &[u8]) -> Result<Nsf, NesError> {
+    let mut nsf = if data.starts_with(NSF_MAGIC) {
+        parse_nsf(data)?
+    } else if data.starts_with(NSFE_MAGIC) {
+        parse_nsfe(data)?
+    } else {
+        return Err(NesError::BadMagic);
+    };
+
+    if nsf.load_addr < LOAD_ADDR_MIN {
+        return Err(NesError::BadNsf(format!(
+            "unsupported load address {:04X}",
+            nsf.load_addr
+        )));
+    }
+
+    if nsf.num_songs == 0 {
+        return Err(NesError::BadNsf(String::from("no songs")));
+    }
+
+    // Some rips give a start song past the last one, INIT would be called
+    // with a song that doesn't exist.
+    nsf.start_song = nsf.start_song.min(nsf.num_songs - 1);
+
+    Ok(nsf)
+}
+
+pub fn nsf_get_info(nsf: &Nsf) -> String {
+    format!(
+        "Title:        {}\
+        \nArtist:       {}\
+        \nCopyright:    {}\
+        \nSongs:        {}\
+        \nLoad Address: {:04X}\
+        \nInit Address: {:04X}\
+        \nPlay Address: {:04X}\
+        \nBankswitched: {}\
+        \nExpansion:    {}",
+        nsf.title,
+        nsf.artist,
+        nsf.copyright,
+        nsf.num_songs,
+        nsf.load_addr,
+        nsf.init_addr,
+        nsf.play_addr,
+        nsf.bankswitched(),
+        expansion_names(nsf.expansion)
+    )
+}
+
+fn parse_nsf(data: &[u8]) -> Result<Nsf, NesError> {
+    if data.len() < NSF_HDR_SIZE {
+        return Err(NesError::TruncatedFile {
+            expected: NSF_HDR_SIZE,
+            actual: data.len(),
+        });
+    }
+
+    let version = data[0x05];
+    let data_len = u32_from_le(&[data[0x7D], data[0x7E], data[0x7F], 0]) as usize;
+    let data_end = if version >= 2 && data_len != 0 {
+        (NSF_HDR_SIZE + data_len).min(data.len())
+    } else {
+        data.len()
+    };
+
+    let mut bank_init = [0; NUM_BANKS];
+    bank_init.copy_from_slice(&data[0x70..0x78]);
+
+    Ok(Nsf {
+        data: data[NSF_HDR_SIZE..data_end].to_vec(),
+        load_addr: u16_from_le(&data[0x08..]),
+        init_addr: u16_from_le(&data[0x0A..]),
+        play_addr: u16_from_le(&data[0x0C..]),
+        num_songs: data[0x06],
+        start_song: data[0x07].saturating_sub(1),
+        title: nsf_string(&data[0x0E..0x0E + NSF_STR_SIZE]),
+        artist: nsf_string(&data[0x2E..0x2E + NSF_STR_SIZE]),
+        copyright: nsf_string(&data[0x4E..0x4E + NSF_STR_SIZE]),
+        play_speed_ntsc: u16_from_le(&data[0x6E..]),
+        play_speed_pal: u16_from_le(&data[0x78..]),
+        bank_init,
+        pal: (data[0x7A] & 0x1) == 0x1,
+        dual_region: (data[0x7A] & 0x2) == 0x2,
+        expansion: data[0x7B],
+    })
+}
+
+fn parse_nsfe(data: &[u8]) -> Result<Nsf, NesError> {
+    let mut nsf = Nsf {
+        data: vec![],
+        load_addr: 0,
+        init_addr: 0,
+        play_addr: 0,
+        num_songs: 1,
+        start_song: 0,
+        title: String::new(),
+        artist: String::new(),
+        copyright: String::new(),
+        play_speed_ntsc: DEFAULT_PLAY_SPEED_NTSC,
+        play_speed_pal: DEFAULT_PLAY_SPEED_PAL,
+        bank_init: [0; NUM_BANKS],
+        pal: false,
+        dual_region: false,
+        expansion: 0,
+    };
+    let mut info_found = false;
+    let mut data_found = false;
+    let mut offset = NSFE_MAGIC.len();
+
+    while offset + NSFE_CHUNK_HDR_SIZE <= data.len() {
+        let chunk_len = u32_from_le(&data[offset..]) as usize;
+        let chunk_id = &data[offset + 4..offset + 8];
+        let chunk_start = offset + NSFE_CHUNK_HDR_SIZE;
+        let chunk_end = chunk_start + chunk_len;
+
+        if chunk_end > data.len() {
+            return Err(NesError::TruncatedFile {
+                expected: chunk_end,
+                actual: data.len(),
+            });
+        }
+
+        let chunk = &data[chunk_start..chunk_end];
+
+        match chunk_id {
+            b"INFO" => {
+                if chunk.len() < NSFE_INFO_MIN_SIZE {
+                    return Err(NesError::BadNsf(String::from("INFO chunk is too short")));
+                }
+
+                nsf.load_addr = u16_from_le(&chunk[0..]);
+                nsf.init_addr = u16_from_le(&chunk[2..]);
+                nsf.play_addr = u16_from_le(&chunk[4..]);
+                nsf.pal = (chunk[6] & 0x1) == 0x1;
+                nsf.dual_region = (chunk[6] & 0x2) == 0x2;
+                nsf.expansion = chunk[7];
+                nsf.num_songs = chunk.get(8).copied().unwrap_or(1);
+                nsf.start_song = chunk.get(9).copied().unwrap_or(0);
+                info_found = true;
+            }
+            b"DATA" => {
+                nsf.data = chunk.to_vec();
+                data_found = true;
+            }
+            b"BANK" => {
+                let len = chunk.len().min(NUM_BANKS);
+                nsf.bank_init[..len].copy_from_slice(&chunk[..len]);
+            }
+            b"RATE" => {
+                if chunk.len() >= 2 {
+                    nsf.play_speed_ntsc = u16_from_le(&chunk[0..]);
+                }
+                if chunk.len() >= 4 {
+                    nsf.play_speed_pal = u16_from_le(&chunk[2..]);
+                }
+            }
+            b"auth" => {
+                let mut strs = chunk.split(|&b| b == 0).map(nsf_string);
+                nsf.title = strs.next().unwrap_or_default();
+                nsf.artist = strs.next().unwrap_or_default();
+                nsf.copyright = strs.next().unwrap_or_default();
+            }
+            b"NEND" => break,
+            _ => {
+                if chunk_id[0].is_ascii_uppercase() {
+                    return Err(NesError::BadNsf(format!(
+                        "unsupported required chunk {}",
+                        String::from_utf8_lossy(chunk_id)
+                    )));
+                }
+            }
+        }
+
+        offset = chunk_end;
+    }
+
+    if !info_found || !data_found {
+        return Err(NesError::BadNsf(String::from("missing INFO or DATA chunk")));
+    }
+
+    Ok(nsf)
+}
+
+fn expansion_names(expansion: u8) -> String {
+    let names: Vec<&str> = [
+        (EXP_VRC6, "VRC6"),
+        (EXP_VRC7, "VRC7"),
+        (EXP_FDS, "FDS"),
+        (EXP_MMC5, "MMC5"),
+        (EXP_N163, "N163"),
+        (EXP_S5B, "S5B"),
+    ]
+    .iter()
+    .filter(|(mask, _)| expansion & mask != 0)
+    .map(|(_, name)| *name)
+    .collect();
+
+    if names.is_empty() {
+        String::from("None")
+    } else {
+        names.join(", ")
+    }
+}
+
+fn nsf_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn u16_from_le(bytes: &[u8]) -> u16 {
+    ((bytes[1] as u16) << 8) | (bytes[0] as u16)
+}
+
+fn u32_from_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
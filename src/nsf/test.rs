@@ -0,0 +1,143 @@
+use super::*;
+
+fn nsf_header() -> Vec<u8> {
+    let mut hdr = vec![0; NSF_HDR_SIZE];
+    hdr[..5].copy_from_slice(NSF_MAGIC);
+    hdr[0x05] = 1;
+    hdr[0x06] = 3;
+    hdr[0x07] = 2;
+    hdr[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x03, 0x80, 0x06, 0x80]);
+    hdr[0x0E..0x13].copy_from_slice(b"Title");
+    hdr[0x2E..0x34].copy_from_slice(b"Artist");
+    hdr[0x6E..0x70].copy_from_slice(&16639u16.to_le_bytes());
+    hdr[0x7A] = 0x1;
+    hdr[0x7B] = EXP_VRC6 | EXP_FDS;
+    hdr
+}
+
+fn nsfe_chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut chunk = (data.len() as u32).to_le_bytes().to_vec();
+    chunk.extend(id);
+    chunk.extend(data);
+    chunk
+}
+
+#[test]
+fn nsf() {
+    let mut data = nsf_header();
+    data.extend([0xEA; 16]);
+
+    let nsf = nsf_parse(&data).unwrap();
+    assert_eq!(
+        (nsf.load_addr, nsf.init_addr, nsf.play_addr),
+        (0x8000, 0x8003, 0x8006)
+    );
+    assert_eq!((nsf.num_songs, nsf.start_song), (3, 1));
+    assert_eq!(
+        (nsf.title.as_str(), nsf.artist.as_str()),
+        ("Title", "Artist")
+    );
+    assert_eq!(nsf.copyright, "");
+    assert_eq!(nsf.play_speed_ntsc, 16639);
+    assert!(nsf.pal && !nsf.dual_region && !nsf.bankswitched());
+    assert_eq!(nsf.region(), Region::Pal);
+    assert_eq!(expansion_names(nsf.expansion), "VRC6, FDS");
+    assert_eq!(nsf.data, [0xEA; 16]);
+}
+
+#[test]
+fn start_song_clamped() {
+    let mut data = nsf_header();
+    data[0x07] = 9;
+    assert_eq!(nsf_parse(&data).unwrap().start_song, 2);
+
+    let mut data = NSFE_MAGIC.to_vec();
+    data.extend(nsfe_chunk(
+        b"INFO",
+        &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0, 0, 2, 5],
+    ));
+    data.extend(nsfe_chunk(b"DATA", &[0xEA]));
+    assert_eq!(nsf_parse(&data).unwrap().start_song, 1);
+}
+
+#[test]
+fn nsf_v2_data_len() {
+    let mut data = nsf_header();
+    data[0x05] = 2;
+    data[0x7D] = 4;
+    data.extend([0xEA; 4]);
+    data.extend(b"metadata");
+
+    assert_eq!(nsf_parse(&data).unwrap().data, [0xEA; 4]);
+}
+
+#[test]
+fn nsf_errors() {
+    assert_eq!(nsf_parse(b"NESN").err(), Some(NesError::BadMagic));
+    assert_eq!(
+        nsf_parse(&nsf_header()[..0x40]).err(),
+        Some(NesError::TruncatedFile {
+            expected: NSF_HDR_SIZE,
+            actual: 0x40
+        })
+    );
+
+    let mut data = nsf_header();
+    data[0x06] = 0;
+    assert!(matches!(nsf_parse(&data), Err(NesError::BadNsf(_))));
+
+    let mut data = nsf_header();
+    data[0x09] = 0x60;
+    assert!(matches!(nsf_parse(&data), Err(NesError::BadNsf(_))));
+}
+
+#[test]
+fn nsfe() {
+    let mut data = NSFE_MAGIC.to_vec();
+    data.extend(nsfe_chunk(
+        b"INFO",
+        &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0x02, EXP_N163, 5, 4],
+    ));
+    data.extend(nsfe_chunk(b"BANK", &[0, 1, 2]));
+    data.extend(nsfe_chunk(b"RATE", &[0x1A, 0x41, 0x1D, 0x4E]));
+    data.extend(nsfe_chunk(b"auth", b"Title\0Artist\0Copyright\0Ripper\0"));
+    data.extend(nsfe_chunk(b"tlbl", b"ignored"));
+    data.extend(nsfe_chunk(b"DATA", &[0xEA; 8]));
+    data.extend(nsfe_chunk(b"NEND", &[]));
+
+    let nsf = nsf_parse(&data).unwrap();
+    assert_eq!((nsf.num_songs, nsf.start_song), (5, 4));
+    assert_eq!(nsf.bank_init, [0, 1, 2, 0, 0, 0, 0, 0]);
+    assert!(nsf.bankswitched() && nsf.dual_region && !nsf.pal);
+    assert_eq!(nsf.region(), Region::Ntsc);
+    assert_eq!((nsf.play_speed_ntsc, nsf.play_speed_pal), (0x411A, 0x4E1D));
+    assert_eq!(nsf.copyright, "Copyright");
+    assert_eq!(nsf.expansion, EXP_N163);
+    assert_eq!(nsf.data, [0xEA; 8]);
+}
+
+#[test]
+fn nsfe_errors() {
+    let info = nsfe_chunk(b"INFO", &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0, 0]);
+    let nsfe = |chunks: &[&[u8]]| [&[NSFE_MAGIC], chunks].concat().concat();
+
+    assert!(matches!(
+        nsf_parse(&nsfe(&[&info])),
+        Err(NesError::BadNsf(_))
+    ));
+    assert!(matches!(
+        nsf_parse(&nsfe(&[
+            &info,
+            &nsfe_chunk(b"VRC7", &[]),
+            &nsfe_chunk(b"DATA", &[0])
+        ])),
+        Err(NesError::BadNsf(_))
+    ));
+
+    let mut truncated = nsfe(&[&info, &nsfe_chunk(b"DATA", &[0; 8])]);
+    truncated.truncate(truncated.len() - 2);
+    assert!(matches!(
+        nsf_parse(&truncated),
+        Err(NesError::TruncatedFile { .. })
+    ));
+}
@@ -0,0 +1,196 @@
+#[cfg(test)]
+mod test;
+
+use crate::palette::{
+    composite_signal, gamma_correct, subcarrier_angle, yiq_to_rgb, NtscParams, FULL_PALETTE_SIZE,
+    SUBCARRIER_PHASES,
+};
+use crate::ppu::NUM_COLS;
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FRAME_SIZE_PIXELS};
+
+pub const NTSC_WIDTH: usize = 602;
+pub const NTSC_FRAME_SIZE_BYTES: usize = NTSC_WIDTH * DISPLAY_HEIGHT as usize * 3;
+const SAMPLES_PER_PIXEL: usize = 8;
+const SAMPLES_PER_ROW: usize = DISPLAY_WIDTH as usize * SAMPLES_PER_PIXEL;
+/// Subcarrier phase change from one scanline (or frame) to the next.
+const ROW_PHASE_STEP: usize = (NUM_COLS as usize * SAMPLES_PER_PIXEL) % SUBCARRIER_PHASES;
+const GAMMA_TABLE_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NtscFilterParams {
+    /// -1.0 (blurry) to 1.0 (sharp), by narrowing the luma filter.
+    pub sharpness: f32,
+    /// 0.0 to 1.0, how much chroma bleeds into luma as dot crawl and
+    /// checkerboard artifacts.
+    pub artifacts: f32,
+    /// 0.0 to 1.0, how much luma edges bleed into chroma as colour fringes.
+    pub fringing: f32,
+    pub picture: NtscParams,
+}
+
+impl Default for NtscFilterParams {
+    fn default() -> Self {
+        Self {
+            sharpness: 0.0,
+            artifacts: 1.0,
+            fringing: 1.0,
+            picture: NtscParams::default(),
+        }
+    }
+}
+
+/// Turns frames of 9-bit pixel values (see `Nes::frame_indices`) into
+/// `NTSC_WIDTH` wide RGB images, by generating each scanline's composite
+/// signal at the master clock rate and decoding it like a TV would. The
+/// subcarrier phase moves on every scanline and frame, giving dot crawl.
+pub struct NtscFilter {
+    params: NtscFilterParams,
+    luma_width: usize,
+    signal_table: Vec<[f32; SUBCARRIER_PHASES]>,
+    cos_table: [f32; SUBCARRIER_PHASES],
+    sin_table: [f32; SUBCARRIER_PHASES],
+    gamma_table: Vec<u8>,
+    frame_phase: usize,
+    signal: Vec<f32>,
+    luma: Vec<f32>,
+    i: Vec<f32>,
+    q: Vec<f32>,
+    scratch: Vec<f32>,
+    sums: Vec<f32>,
+}
+
+impl NtscFilter {
+    pub fn init(params: NtscFilterParams) -> Self {
+        let signal_table = (0..FULL_PALETTE_SIZE)
+            .map(|pixel| std::array::from_fn(|phase| composite_signal(pixel, phase)))
+            .collect();
+        let cos_table =
+            std::array::from_fn(|phase| subcarrier_angle(phase, params.picture.hue).cos());
+        let sin_table =
+            std::array::from_fn(|phase| subcarrier_angle(phase, params.picture.hue).sin());
+        let gamma_table = (0..GAMMA_TABLE_SIZE)
+            .map(|n| {
+                gamma_correct(
+                    n as f32 / (GAMMA_TABLE_SIZE - 1) as f32,
+                    params.picture.gamma,
+                )
+            })
+            .collect();
+        let luma_width =
+            (SUBCARRIER_PHASES as f32 * (1.0 - (params.sharpness.clamp(-1.0, 1.0) * 0.5))).round();
+
+        Self {
+            params,
+            luma_width: luma_width as usize,
+            signal_table,
+            cos_table,
+            sin_table,
+            gamma_table,
+            frame_phase: 0,
+            signal: vec![0.0; SAMPLES_PER_ROW],
+            luma: vec![0.0; SAMPLES_PER_ROW],
+            i: vec![0.0; SAMPLES_PER_ROW],
+            q: vec![0.0; SAMPLES_PER_ROW],
+            scratch: vec![0.0; SAMPLES_PER_ROW],
+            sums: vec![0.0; SAMPLES_PER_ROW + 1],
+        }
+    }
+
+    /// Filters a frame into `rgb`, which must hold `NTSC_FRAME_SIZE_BYTES`.
+    pub fn filter(&mut self, indices: &[u16; FRAME_SIZE_PIXELS], rgb: &mut [u8]) {
+        let width = DISPLAY_WIDTH as usize;
+
+        for (row, (pixels, rgb_row)) in indices
+            .chunks_exact(width)
+            .zip(rgb.chunks_exact_mut(NTSC_WIDTH * 3))
+            .enumerate()
+        {
+            let row_phase = (self.frame_phase + (row * ROW_PHASE_STEP)) % SUBCARRIER_PHASES;
+            self.filter_row(pixels, row_phase, rgb_row);
+        }
+
+        self.frame_phase = (self.frame_phase + ROW_PHASE_STEP) % SUBCARRIER_PHASES;
+    }
+
+    fn filter_row(&mut self, pixels: &[u16], row_phase: usize, rgb: &mut [u8]) {
+        let phase = |n: usize| (row_phase + n) % SUBCARRIER_PHASES;
+
+        for (n, signal) in self.signal.iter_mut().enumerate() {
+            let pixel = pixels[n / SAMPLES_PER_PIXEL] as usize % FULL_PALETTE_SIZE;
+            *signal = self.signal_table[pixel][phase(n)];
+        }
+
+        // Chroma is demodulated over one subcarrier cycle, from a signal
+        // with some of its luma removed to limit fringing.
+        box_filter(
+            &self.signal,
+            SUBCARRIER_PHASES,
+            &mut self.sums,
+            &mut self.luma,
+        );
+        let luma_leak = 1.0 - self.params.fringing;
+
+        for (n, scratch) in self.scratch.iter_mut().enumerate() {
+            *scratch = (self.signal[n] - (luma_leak * self.luma[n])) * self.cos_table[phase(n)];
+        }
+        box_filter(
+            &self.scratch,
+            SUBCARRIER_PHASES,
+            &mut self.sums,
+            &mut self.i,
+        );
+
+        for (n, scratch) in self.scratch.iter_mut().enumerate() {
+            *scratch = (self.signal[n] - (luma_leak * self.luma[n])) * self.sin_table[phase(n)];
+        }
+        box_filter(
+            &self.scratch,
+            SUBCARRIER_PHASES,
+            &mut self.sums,
+            &mut self.q,
+        );
+
+        // Luma is what's left after the decoded chroma is taken out, with
+        // any remainder showing up as artifacts.
+        let chroma_removed = 1.0 - self.params.artifacts;
+
+        for (n, scratch) in self.scratch.iter_mut().enumerate() {
+            let chroma = 2.0 * (self.i[n] * self.cos_table[phase(n)])
+                + 2.0 * (self.q[n] * self.sin_table[phase(n)]);
+            *scratch = self.signal[n] - (chroma_removed * chroma);
+        }
+        box_filter(
+            &self.scratch,
+            self.luma_width,
+            &mut self.sums,
+            &mut self.luma,
+        );
+
+        for (x, pixel) in rgb.chunks_exact_mut(3).enumerate() {
+            let n = (((x * 2) + 1) * SAMPLES_PER_ROW) / (NTSC_WIDTH * 2);
+            let channels = yiq_to_rgb(self.luma[n], self.i[n], self.q[n], &self.params.picture);
+
+            for (out, val) in pixel.iter_mut().zip(channels) {
+                let idx = (val * (GAMMA_TABLE_SIZE - 1) as f32) as isize;
+                *out = self.gamma_table[idx.clamp(0, GAMMA_TABLE_SIZE as isize - 1) as usize];
+            }
+        }
+    }
+}
+
+/// Averages `input` over a window of `width` samples centred on each sample,
+/// clamping at the edges.
+fn box_filter(input: &[f32], width: usize, sums: &mut [f32], output: &mut [f32]) {
+    let width = width.max(1);
+
+    sums[0] = 0.0;
+    for (n, &val) in input.iter().enumerate() {
+        sums[n + 1] = sums[n] + val;
+    }
+
+    for (n, out) in output.iter_mut().enumerate() {
+        let start = n.saturating_sub(width / 2);
+        let end = (start + width).min(input.len());
+        *out = (sums[end] - sums[start]) / (end - start) as f32;
+    }
+}
@@ -0,0 +1,68 @@
+use super::*;
+use crate::palette::{ntsc_palette, Rgb};
+use crate::FRAME_SIZE_PIXELS;
+
+fn filter_frame(filter: &mut NtscFilter, indices: &[u16; FRAME_SIZE_PIXELS]) -> Vec<u8> {
+    let mut rgb = vec![0; NTSC_FRAME_SIZE_BYTES];
+    filter.filter(indices, &mut rgb);
+    rgb
+}
+
+#[test]
+fn flat_colour_matches_palette() {
+    let params = NtscFilterParams::default();
+    let palette = ntsc_palette(&params.picture);
+    let mut filter = NtscFilter::init(params);
+
+    for pixel in [0x0F, 0x16, 0x21, 0x2A, 0x30, 0x1B0] {
+        let rgb = filter_frame(&mut filter, &[pixel; FRAME_SIZE_PIXELS]);
+        let Rgb(r, g, b) = palette[pixel as usize];
+        let idx = ((100 * NTSC_WIDTH) + (NTSC_WIDTH / 2)) * 3;
+
+        for (out, expected) in rgb[idx..idx + 3].iter().zip([r, g, b]) {
+            assert!(out.abs_diff(expected) <= 2, "{:03X}", pixel);
+        }
+    }
+}
+
+#[test]
+fn dot_crawl() {
+    let mut indices = [0x0F; FRAME_SIZE_PIXELS];
+    for (n, pixel) in indices.iter_mut().enumerate() {
+        if (n / 2) % 2 == 0 {
+            *pixel = 0x30;
+        }
+    }
+
+    let mut filter = NtscFilter::init(NtscFilterParams::default());
+    let frames: Vec<Vec<u8>> = (0..4)
+        .map(|_| filter_frame(&mut filter, &indices))
+        .collect();
+    assert_ne!(frames[0], frames[1]);
+    assert_eq!(frames[0], frames[3]);
+}
+
+#[test]
+fn fringing() {
+    let mut indices = [0x0F; FRAME_SIZE_PIXELS];
+    for (n, pixel) in indices.iter_mut().enumerate() {
+        if n % DISPLAY_WIDTH as usize >= 128 {
+            *pixel = 0x30;
+        }
+    }
+
+    let colourfulness = |fringing: f32| {
+        let mut filter = NtscFilter::init(NtscFilterParams {
+            fringing,
+            ..Default::default()
+        });
+        let rgb = filter_frame(&mut filter, &indices);
+        rgb[..NTSC_WIDTH * 3]
+            .chunks_exact(3)
+            .map(|pixel| pixel.iter().max().unwrap() - pixel.iter().min().unwrap())
+            .map(|diff| diff as u32)
+            .sum::<u32>()
+    };
+
+    assert!(colourfulness(0.0) < colourfulness(1.0));
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod test;
+
+use std::f32::consts::PI;
+
+pub const PALETTE_SIZE: usize = 64;
+pub const NUM_EMPHASIS: usize = 8;
+pub const FULL_PALETTE_SIZE: usize = PALETTE_SIZE * NUM_EMPHASIS;
+const EMPHASIS_ATTENUATION: f32 = 0.816328;
+
+// Composite signal levels, relative to sync, from the 2C02's DAC.
+const SIGNAL_LOW: [f32; 4] = [0.350, 0.518, 0.962, 1.550];
+const SIGNAL_HIGH: [f32; 4] = [1.094, 1.506, 1.962, 1.962];
+const SIGNAL_BLACK: f32 = 0.518;
+const SIGNAL_WHITE: f32 = 1.962;
+const SIGNAL_ATTENUATION: f32 = 0.746;
+/// The PPU generates colours from 12 phases of the colour subcarrier, one per
+/// master clock cycle.
+pub const SUBCARRIER_PHASES: usize = 12;
+/// Phase of the colour burst, in master clock cycles, so that hue 6 decodes
+/// as red.
+const BURST_PHASE: f32 = 3.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// A colour for every combination of the 6-bit palette index and the 3
+/// emphasis bits, indexed by `(emphasis << 6) | colour`.
+pub type Palette = [Rgb; FULL_PALETTE_SIZE];
+
+/// Parameters for `ntsc_palette`. The defaults give a neutral palette close
+/// to a typical TV.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NtscParams {
+    /// Hue rotation in degrees.
+    pub hue: f32,
+    pub saturation: f32,
+    pub contrast: f32,
+    /// Added to the luma, so 0.0 is unchanged.
+    pub brightness: f32,
+    /// The display's gamma.
+    pub gamma: f32,
+}
+
+impl Default for NtscParams {
+    fn default() -> Self {
+        Self {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            brightness: 0.0,
+            gamma: 1.8,
+        }
+    }
+}
+
+/// Parses a `.pal` file of 64 or 512 RGB triplets. 64 colour palettes have
+/// their emphasis variants computed with `emphasis_palette`.
+pub fn pal_parse(data: &[u8]) -> Result<Palette, String> {
+    let colours: Vec<Rgb> = data
+        .chunks_exact(3)
+        .map(|rgb| Rgb(rgb[0], rgb[1], rgb[2]))
+        .collect();
+
+    match (colours.len(), data.len() % 3) {
+        (PALETTE_SIZE, 0) => Ok(emphasis_palette(colours[..].try_into().unwrap())),
+        (FULL_PALETTE_SIZE, 0) => Ok(colours[..].try_into().unwrap()),
+        _ => Err(format!(
+            "Palette must have {} or {} colours, file is {} bytes.",
+            PALETTE_SIZE,
+            FULL_PALETTE_SIZE,
+            data.len()
+        )),
+    }
+}
+
+/// Converts 9-bit pixel values, as output by `Nes::frame_indices`, to packed
+/// RGB.
+pub fn indices_to_rgb(indices: &[u16], palette: &Palette, rgb: &mut [u8]) {
+    for (&idx, pixel) in indices.iter().zip(rgb.chunks_exact_mut(3)) {
+        let Rgb(r, g, b) = palette[idx as usize % FULL_PALETTE_SIZE];
+        pixel.copy_from_slice(&[r, g, b]);
+    }
+}
+
+/// Extends a 64 colour palette with emphasis. Each emphasis bit (red, green,
+/// blue from bit 0) darkens the two channels it doesn't emphasise.
+pub fn emphasis_palette(palette: &[Rgb; PALETTE_SIZE]) -> Palette {
+    let mut full = [Rgb(0, 0, 0); FULL_PALETTE_SIZE];
+
+    for (idx, rgb) in full.iter_mut().enumerate() {
+        let emphasis = idx / PALETTE_SIZE;
+        let Rgb(r, g, b) = palette[idx % PALETTE_SIZE];
+        let attenuate = |val: u8, channel: usize| {
+            let other_bits = (emphasis & !(1 << channel)).count_ones() as i32;
+            (val as f32 * EMPHASIS_ATTENUATION.powi(other_bits)).round() as u8
+        };
+
+        *rgb = Rgb(attenuate(r, 0), attenuate(g, 1), attenuate(b, 2));
+    }
+
+    full
+}
+
+/// Generates a palette by modelling the PPU's composite output and decoding
+/// it as an ideal NTSC TV would, averaging each colour over a full cycle of
+/// the subcarrier.
+pub fn ntsc_palette(params: &NtscParams) -> Palette {
+    let mut palette = [Rgb(0, 0, 0); FULL_PALETTE_SIZE];
+
+    for (pixel, rgb) in palette.iter_mut().enumerate() {
+        let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+
+        for phase in 0..SUBCARRIER_PHASES {
+            let signal = composite_signal(pixel, phase) / SUBCARRIER_PHASES as f32;
+            let angle = subcarrier_angle(phase, params.hue);
+            y += signal;
+            i += signal * angle.cos();
+            q += signal * angle.sin();
+        }
+
+        let [r, g, b] = yiq_to_rgb(y, i, q, params);
+        *rgb = Rgb(
+            gamma_correct(r, params.gamma),
+            gamma_correct(g, params.gamma),
+            gamma_correct(b, params.gamma),
+        );
+    }
+
+    palette
+}
+
+/// The PPU's composite output for a 9-bit pixel value at one phase of the
+/// subcarrier, normalised so that black is 0.0 and white 1.0. Each colour is
+/// a square wave between two levels, in phase with one of 12 hues, and
+/// emphasis attenuates the signal during the phases of the emphasised
+/// colours.
+pub(crate) fn composite_signal(pixel: usize, phase: usize) -> f32 {
+    let hue = pixel & 0x0F;
+    let level = if hue > 0x0D { 1 } else { (pixel >> 4) & 0x3 };
+    let emphasis = (pixel >> 6) & 0x7;
+    let in_phase = |hue: usize| (hue + phase) % SUBCARRIER_PHASES < SUBCARRIER_PHASES / 2;
+
+    let mut signal = match hue {
+        0x00 => SIGNAL_HIGH[level],
+        0x0D..=0x0F => SIGNAL_LOW[level],
+        _ if in_phase(hue) => SIGNAL_HIGH[level],
+        _ => SIGNAL_LOW[level],
+    };
+
+    if (emphasis & 0x1 != 0 && in_phase(0))
+        || (emphasis & 0x2 != 0 && in_phase(4))
+        || (emphasis & 0x4 != 0 && in_phase(8))
+    {
+        signal *= SIGNAL_ATTENUATION;
+    }
+
+    (signal - SIGNAL_BLACK) / (SIGNAL_WHITE - SIGNAL_BLACK)
+}
+
+/// The angle a TV demodulates chroma at for a phase of the subcarrier.
+pub(crate) fn subcarrier_angle(phase: usize, hue: f32) -> f32 {
+    (PI / 6.0) * (phase as f32 + BURST_PHASE) + hue.to_radians()
+}
+
+/// Converts YIQ to linear RGB, applying the picture settings.
+pub(crate) fn yiq_to_rgb(y: f32, i: f32, q: f32, params: &NtscParams) -> [f32; 3] {
+    let y = (y * params.contrast) + params.brightness;
+    let i = i * params.saturation * params.contrast;
+    let q = q * params.saturation * params.contrast;
+
+    [
+        y + (0.946882 * i) + (0.623557 * q),
+        y - (0.274788 * i) - (0.635691 * q),
+        y - (1.108545 * i) + (1.709007 * q),
+    ]
+}
+
+pub(crate) fn gamma_correct(val: f32, gamma: f32) -> u8 {
+    let val = if val <= 0.0 {
+        0.0
+    } else {
+        val.powf(2.2 / gamma)
+    };
+    (val * 255.0).round().clamp(0.0, 255.0) as u8
+}
@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn pal_parse_sizes() {
+    let mut data: Vec<u8> = (0..PALETTE_SIZE * 3).map(|n| n as u8).collect();
+    let palette = pal_parse(&data).unwrap();
+    assert_eq!(palette[0x01], Rgb(3, 4, 5));
+    assert_eq!(palette[0x3F], Rgb(189, 190, 191));
+    assert!(palette[0x13F].0 < 189);
+
+    data.resize(FULL_PALETTE_SIZE * 3, 0x80);
+    let palette = pal_parse(&data).unwrap();
+    assert_eq!(palette[0x01], Rgb(3, 4, 5));
+    assert_eq!(palette[0x1FF], Rgb(0x80, 0x80, 0x80));
+
+    assert!(pal_parse(&data[..100]).is_err());
+    assert!(pal_parse(&data[..PALETTE_SIZE * 3 + 1]).is_err());
+}
+
+#[test]
+fn ntsc_palette_defaults() {
+    let palette = ntsc_palette(&NtscParams::default());
+
+    assert_eq!(palette[0x0F], Rgb(0, 0, 0));
+    assert_eq!(palette[0x30], Rgb(255, 255, 255));
+
+    // Greys are neutral, and brighten with each level.
+    let Rgb(r, g, b) = palette[0x10];
+    assert!(r.abs_diff(g) <= 1 && g.abs_diff(b) <= 1);
+    assert!(palette[0x00].0 < palette[0x10].0);
+
+    // Hue 6 is red, hue A green and hue 2 blue, and red emphasis dims green
+    // and blue the most.
+    let Rgb(r, g, b) = palette[0x16];
+    assert!(r > g && r > b);
+    let Rgb(r, g, b) = palette[0x1A];
+    assert!(g > r && g > b);
+    let Rgb(r, g, b) = palette[0x12];
+    assert!(b > r && b > g);
+    let Rgb(r_emph, g_emph, b_emph) = palette[0x40 | 0x20];
+    let Rgb(r_base, g_base, b_base) = palette[0x20];
+    assert!(g_base - g_emph > r_base - r_emph);
+    assert!(b_base - b_emph > r_base - r_emph);
+}
+
+#[test]
+fn indices_to_rgb_uses_emphasis() {
+    let palette = ntsc_palette(&NtscParams::default());
+    let mut rgb = [0; 6];
+    indices_to_rgb(&[0x30, 0x1B0], &palette, &mut rgb);
+
+    let (Rgb(r0, g0, b0), Rgb(r1, g1, b1)) = (palette[0x30], palette[0x1B0]);
+    assert_eq!(rgb, [r0, g0, b0, r1, g1, b1]);
+}
@@ -0,0 +1,264 @@
+#[cfg(test)]
+mod test;
+
+use crate::hash::crc32;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const UPS_MAGIC: &[u8] = b"UPS1";
+const BPS_MAGIC: &[u8] = b"BPS1";
+const FOOTER_SIZE: usize = 12;
+
+const BPS_SOURCE_READ: usize = 0;
+const BPS_TARGET_READ: usize = 1;
+const BPS_SOURCE_COPY: usize = 2;
+const BPS_TARGET_COPY: usize = 3;
+
+/// Applies an IPS, UPS or BPS patch to `data`, detected from the patch's
+/// magic bytes.
+pub fn patch_apply(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    if patch.starts_with(IPS_MAGIC) {
+        ips_apply(data, patch)
+    } else if patch.starts_with(UPS_MAGIC) {
+        ups_apply(data, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        bps_apply(data, patch)
+    } else {
+        Err(String::from("Unknown patch format."))
+    }
+}
+
+pub fn ips_apply(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = PatchReader::new(patch, IPS_MAGIC.len());
+    let mut target = data.to_vec();
+
+    loop {
+        if reader.remaining() == IPS_EOF {
+            break;
+        }
+
+        if reader.remaining().starts_with(IPS_EOF) && reader.remaining().len() == 6 {
+            reader.bytes(IPS_EOF.len())?;
+            let len = reader.u24_be()? as usize;
+            target.truncate(len);
+            break;
+        }
+
+        let offset = reader.u24_be()? as usize;
+        let size = reader.u16_be()? as usize;
+
+        let (bytes, size) = if size == 0 {
+            let rle_size = reader.u16_be()? as usize;
+            let val = reader.byte()?;
+            (vec![val; rle_size], rle_size)
+        } else {
+            (reader.bytes(size)?.to_vec(), size)
+        };
+
+        if target.len() < offset + size {
+            target.resize(offset + size, 0);
+        }
+        target[offset..offset + size].copy_from_slice(&bytes);
+    }
+
+    Ok(target)
+}
+
+pub fn ups_apply(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let (_, target_crc) = check_footer(data, patch)?;
+    let mut reader = PatchReader::new(&patch[..patch.len() - FOOTER_SIZE], UPS_MAGIC.len());
+
+    let source_size = reader.varint()?;
+    let target_size = reader.varint()?;
+
+    if data.len() != source_size {
+        return Err(String::from("Source size does not match patch."));
+    }
+
+    let mut target = data.to_vec();
+    target.resize(target_size, 0);
+    let mut pos = 0;
+
+    while !reader.remaining().is_empty() {
+        pos += reader.varint()?;
+
+        loop {
+            let xor = reader.byte()?;
+            if xor == 0 {
+                pos += 1;
+                break;
+            }
+
+            if pos < target.len() {
+                target[pos] = data.get(pos).copied().unwrap_or(0) ^ xor;
+            }
+            pos += 1;
+        }
+    }
+
+    check_target(&target, target_crc)
+}
+
+pub fn bps_apply(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let (_, target_crc) = check_footer(data, patch)?;
+    let mut reader = PatchReader::new(&patch[..patch.len() - FOOTER_SIZE], BPS_MAGIC.len());
+
+    let source_size = reader.varint()?;
+    let target_size = reader.varint()?;
+    let metadata_size = reader.varint()?;
+    reader.bytes(metadata_size)?;
+
+    if data.len() != source_size {
+        return Err(String::from("Source size does not match patch."));
+    }
+
+    let mut target: Vec<u8> = Vec::with_capacity(target_size);
+    let mut source_offset: usize = 0;
+    let mut target_offset: usize = 0;
+
+    while !reader.remaining().is_empty() {
+        let action = reader.varint()?;
+        let len = (action >> 2) + 1;
+
+        match action & 0x3 {
+            BPS_SOURCE_READ => {
+                let start = target.len();
+                let bytes = data
+                    .get(start..start + len)
+                    .ok_or("Source read out of range.")?;
+                target.extend_from_slice(bytes);
+            }
+            BPS_TARGET_READ => target.extend_from_slice(reader.bytes(len)?),
+            BPS_SOURCE_COPY => {
+                source_offset = apply_rel_offset(source_offset, reader.varint()?)?;
+                let bytes = data
+                    .get(source_offset..source_offset + len)
+                    .ok_or("Source copy out of range.")?;
+                target.extend_from_slice(bytes);
+                source_offset += len;
+            }
+            BPS_TARGET_COPY => {
+                target_offset = apply_rel_offset(target_offset, reader.varint()?)?;
+                for _ in 0..len {
+                    let byte = *target
+                        .get(target_offset)
+                        .ok_or("Target copy out of range.")?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if target.len() != target_size {
+        return Err(String::from("Target size does not match patch."));
+    }
+
+    check_target(&target, target_crc)
+}
+
+/// Checks the patch's own CRC32 and that `data` is the source it was made
+/// for. Returns the source and target CRC32s.
+fn check_footer(data: &[u8], patch: &[u8]) -> Result<(u32, u32), String> {
+    if patch.len() < UPS_MAGIC.len() + FOOTER_SIZE {
+        return Err(String::from("Patch is truncated."));
+    }
+
+    let footer = &patch[patch.len() - FOOTER_SIZE..];
+    let source_crc = u32_from_le(&footer[0..4]);
+    let target_crc = u32_from_le(&footer[4..8]);
+    let patch_crc = u32_from_le(&footer[8..12]);
+
+    if crc32(&patch[..patch.len() - 4]) != patch_crc {
+        return Err(String::from("Patch CRC32 mismatch, the patch is corrupt."));
+    }
+
+    if crc32(data) != source_crc {
+        return Err(String::from(
+            "Source CRC32 mismatch, the patch is for a different ROM.",
+        ));
+    }
+
+    Ok((source_crc, target_crc))
+}
+
+fn check_target(target: &[u8], target_crc: u32) -> Result<Vec<u8>, String> {
+    if crc32(target) != target_crc {
+        return Err(String::from("Target CRC32 mismatch after patching."));
+    }
+
+    Ok(target.to_vec())
+}
+
+fn apply_rel_offset(offset: usize, data: usize) -> Result<usize, String> {
+    let delta = (data >> 1) as isize;
+    let delta = if data & 0x1 == 0x1 { -delta } else { delta };
+
+    offset
+        .checked_add_signed(delta)
+        .ok_or_else(|| String::from("Relative offset out of range."))
+}
+
+fn u32_from_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+struct PatchReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos.min(self.data.len())..]
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| String::from("Patch is truncated."))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16_be(&mut self) -> Result<u16, String> {
+        let bytes = self.bytes(2)?;
+        Ok(((bytes[0] as u16) << 8) | bytes[1] as u16)
+    }
+
+    fn u24_be(&mut self) -> Result<u32, String> {
+        let bytes = self.bytes(3)?;
+        Ok(((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | bytes[2] as u32)
+    }
+
+    /// Reads a UPS/BPS variable-length integer.
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut val: usize = 0;
+        let mut shift: usize = 1;
+
+        loop {
+            let byte = self.byte()?;
+            val = (byte as usize & 0x7F)
+                .checked_mul(shift)
+                .and_then(|bits| val.checked_add(bits))
+                .ok_or("Patch integer overflow.")?;
+
+            if byte & 0x80 != 0 {
+                return Ok(val);
+            }
+
+            shift = shift.checked_shl(7).ok_or("Patch integer overflow.")?;
+            val = val.checked_add(shift).ok_or("Patch integer overflow.")?;
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::*;
+
+fn varint(mut val: usize) -> Vec<u8> {
+    let mut bytes = vec![];
+
+    loop {
+        let x = (val & 0x7F) as u8;
+        val >>= 7;
+        if val == 0 {
+            bytes.push(0x80 | x);
+            return bytes;
+        }
+        bytes.push(x);
+        val -= 1;
+    }
+}
+
+fn with_footer(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+    patch.extend(crc32(source).to_le_bytes());
+    patch.extend(crc32(target).to_le_bytes());
+    patch.extend(crc32(&patch).to_le_bytes());
+    patch
+}
+
+#[test]
+fn ips() {
+    let source = [0u8; 8];
+    let mut patch = b"PATCH".to_vec();
+    patch.extend([0x00, 0x00, 0x02, 0x00, 0x02, 0xAA, 0xBB]);
+    patch.extend([0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x04, 0xCC]);
+    patch.extend(b"EOF");
+
+    assert_eq!(
+        patch_apply(&source, &patch).unwrap(),
+        [0x00, 0x00, 0xAA, 0xBB, 0x00, 0x00, 0xCC, 0xCC, 0xCC, 0xCC]
+    );
+
+    patch.extend([0x00, 0x00, 0x03]);
+    assert_eq!(patch_apply(&source, &patch).unwrap(), [0x00, 0x00, 0xAA]);
+
+    assert!(patch_apply(&source, &patch[..patch.len() - 8]).is_err());
+}
+
+#[test]
+fn varint_round_trip() {
+    for val in [0, 1, 0x7F, 0x80, 0x4000, 0x123456] {
+        let bytes = varint(val);
+        assert_eq!(PatchReader::new(&bytes, 0).varint().unwrap(), val);
+    }
+}
+
+#[test]
+fn ups() {
+    let source = [0x10, 0x20, 0x30, 0x40];
+    let target = [0x10, 0x21, 0x30, 0x40, 0x00, 0x55];
+
+    let mut patch = b"UPS1".to_vec();
+    patch.extend(varint(source.len()));
+    patch.extend(varint(target.len()));
+    patch.extend(varint(1));
+    patch.extend([0x20 ^ 0x21, 0x00]);
+    patch.extend(varint(2));
+    patch.extend([0x55, 0x00]);
+    let patch = with_footer(patch, &source, &target);
+
+    assert_eq!(patch_apply(&source, &patch).unwrap(), target);
+    assert!(patch_apply(&target[..4], &patch).is_err());
+}
+
+#[test]
+fn bps() {
+    let source = [0x01, 0x02, 0x03, 0x04];
+    let target = [0x01, 0x02, 0xEE, 0x03, 0x04, 0x03, 0x04, 0x03];
+
+    let mut patch = b"BPS1".to_vec();
+    patch.extend(varint(source.len()));
+    patch.extend(varint(target.len()));
+    patch.extend(varint(0));
+    patch.extend(varint(((2 - 1) << 2) | BPS_SOURCE_READ));
+    patch.extend(varint(BPS_TARGET_READ));
+    patch.push(0xEE);
+    patch.extend(varint(((2 - 1) << 2) | BPS_SOURCE_COPY));
+    patch.extend(varint(2 << 1));
+    patch.extend(varint(((3 - 1) << 2) | BPS_TARGET_COPY));
+    patch.extend(varint(3 << 1));
+    let patch = with_footer(patch, &source, &target);
+
+    assert_eq!(patch_apply(&source, &patch).unwrap(), target);
+
+    let mut corrupt = patch.clone();
+    corrupt[5] ^= 0xFF;
+    assert!(patch_apply(&source, &corrupt).is_err());
+}
@@ -0,0 +1,222 @@
+#[cfg(test)]
+mod test;
+
+use crate::hash::{adler32, crc32, crc32_update};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const BIT_DEPTH: u8 = 8;
+const COLOR_TYPE_RGB: u8 = 2;
+const FILTER_NONE: u8 = 0;
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x01];
+const BLOCK_FINAL_FIXED: u32 = 0b011;
+const END_OF_BLOCK: u16 = 256;
+
+const WINDOW_SIZE: usize = 0x8000;
+const HASH_BITS: u32 = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Encodes 8-bit RGB pixels, e.g. a frame from `Nes::frame`, as a PNG.
+pub fn png_encode(rgb: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let row_size = width as usize * 3;
+    assert_eq!(rgb.len(), row_size * height as usize);
+
+    let mut raw = Vec::with_capacity((row_size + 1) * height as usize);
+    for row in rgb.chunks_exact(row_size) {
+        raw.push(FILTER_NONE);
+        raw.extend_from_slice(row);
+    }
+
+    let mut ihdr = vec![];
+    ihdr.extend(width.to_be_bytes());
+    ihdr.extend(height.to_be_bytes());
+    ihdr.extend([BIT_DEPTH, COLOR_TYPE_RGB, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    png_chunk(&mut png, b"IHDR", &ihdr);
+    png_chunk(&mut png, b"IDAT", &zlib_compress(&raw));
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    png.extend(crc32_update(crc32(kind), data).to_be_bytes());
+}
+
+/// Compresses the data as a single deflate block with the fixed Huffman
+/// codes, finding repeats with hash chains. Frames are mostly runs of the
+/// same few colours, so this gets most of the way to a dynamic encoder.
+fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter {
+        bytes: ZLIB_HEADER.to_vec(),
+        ..Default::default()
+    };
+    out.bits(BLOCK_FINAL_FIXED, 3);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let (len, dist) = longest_match(data, pos, &head, &prev);
+
+        if len >= MIN_MATCH {
+            out.length(len);
+            out.distance(dist);
+            for pos in pos..pos + len {
+                insert_match(data, pos, &mut head, &mut prev);
+            }
+            pos += len;
+        } else {
+            out.literal(data[pos] as u16);
+            insert_match(data, pos, &mut head, &mut prev);
+            pos += 1;
+        }
+    }
+
+    out.literal(END_OF_BLOCK);
+    out.flush();
+
+    let mut bytes = out.bytes;
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+fn insert_match(data: &[u8], pos: usize, head: &mut [usize], prev: &mut [usize]) {
+    if pos + MIN_MATCH <= data.len() {
+        let hash = match_hash(&data[pos..]);
+        prev[pos % WINDOW_SIZE] = head[hash];
+        head[hash] = pos;
+    }
+}
+
+fn match_hash(data: &[u8]) -> usize {
+    let val = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+    (val.wrapping_mul(0x9E3779B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Follows the chain of earlier positions with the same hash, returning the
+/// longest match's length and distance.
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let max_len = MAX_MATCH.min(data.len() - pos);
+    let (mut best_len, mut best_dist) = (0, 0);
+    let mut candidate = head[match_hash(&data[pos..])];
+
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || pos - candidate > WINDOW_SIZE {
+            break;
+        }
+
+        let len = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max_len])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best_len {
+            (best_len, best_dist) = (len, pos - candidate);
+            if len == max_len {
+                break;
+            }
+        }
+
+        let next = prev[candidate % WINDOW_SIZE];
+        if next == usize::MAX || next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+
+    (best_len, best_dist)
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    num_bits: u32,
+}
+
+impl BitWriter {
+    /// Writes `val` starting from its least significant bit.
+    fn bits(&mut self, val: u32, num_bits: u32) {
+        self.acc |= val << self.num_bits;
+        self.num_bits += num_bits;
+
+        while self.num_bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.num_bits -= 8;
+        }
+    }
+
+    /// Huffman codes are written starting from their most significant bit.
+    fn code(&mut self, code: u32, len: u32) {
+        self.bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn literal(&mut self, sym: u16) {
+        let sym = sym as u32;
+        match sym {
+            0..=143 => self.code(0x30 + sym, 8),
+            144..=255 => self.code(0x190 + sym - 144, 9),
+            256..=279 => self.code(sym - 256, 7),
+            _ => self.code(0xC0 + sym - 280, 8),
+        }
+    }
+
+    fn length(&mut self, len: usize) {
+        let idx = LENGTH_BASE
+            .iter()
+            .rposition(|&base| base as usize <= len)
+            .unwrap();
+        self.literal(257 + idx as u16);
+        self.bits(
+            (len - LENGTH_BASE[idx] as usize) as u32,
+            LENGTH_EXTRA[idx] as u32,
+        );
+    }
+
+    fn distance(&mut self, dist: usize) {
+        let idx = DIST_BASE
+            .iter()
+            .rposition(|&base| base as usize <= dist)
+            .unwrap();
+        self.code(idx as u32, 5);
+        self.bits(
+            (dist - DIST_BASE[idx] as usize) as u32,
+            DIST_EXTRA[idx] as u32,
+        );
+    }
+
+    fn flush(&mut self) {
+        if self.num_bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.acc = 0;
+        self.num_bits = 0;
+    }
+}
@@ -0,0 +1,110 @@
+use super::*;
+
+/// Splits a PNG into its chunks, checking each one's CRC.
+fn png_chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    let mut chunks = vec![];
+    let mut data = &png[PNG_SIGNATURE.len()..];
+
+    while !data.is_empty() {
+        let len = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+        let kind: [u8; 4] = data[4..8].try_into().unwrap();
+        let body = data[8..8 + len].to_vec();
+        let crc = u32::from_be_bytes(data[8 + len..12 + len].try_into().unwrap());
+
+        assert_eq!(crc, crc32(&data[4..8 + len]));
+        chunks.push((kind, body));
+        data = &data[12 + len..];
+    }
+
+    chunks
+}
+
+/// Decompresses a zlib stream made of a single fixed Huffman block.
+fn zlib_decompress(zlib: &[u8]) -> Vec<u8> {
+    let mut bit_pos = ZLIB_HEADER.len() * 8;
+    let mut bits = |num_bits: usize| {
+        let val = (0..num_bits).fold(0, |val, idx| {
+            let pos = bit_pos + idx;
+            val | (((zlib[pos / 8] >> (pos % 8)) as usize & 0x1) << idx)
+        });
+        bit_pos += num_bits;
+        val
+    };
+    let mut code = |bits: &mut dyn FnMut(usize) -> usize, len: usize| {
+        (0..len).fold(0, |code, _| (code << 1) | bits(1))
+    };
+
+    assert_eq!(bits(3), BLOCK_FINAL_FIXED as usize);
+    let mut data = vec![];
+
+    loop {
+        let mut sym = code(&mut bits, 7);
+        sym = match sym {
+            0x00..=0x17 => sym + 256,
+            _ => {
+                sym = (sym << 1) | bits(1);
+                match sym {
+                    0x30..=0xBF => sym - 0x30,
+                    0xC0..=0xC7 => sym - 0xC0 + 280,
+                    _ => ((sym << 1) | bits(1)) - 0x190 + 144,
+                }
+            }
+        };
+
+        match sym {
+            0..=255 => data.push(sym as u8),
+            256 => break,
+            _ => {
+                let idx = sym - 257;
+                let len = LENGTH_BASE[idx] as usize + bits(LENGTH_EXTRA[idx] as usize);
+                let idx = code(&mut bits, 5);
+                let dist = DIST_BASE[idx] as usize + bits(DIST_EXTRA[idx] as usize);
+                for _ in 0..len {
+                    data.push(data[data.len() - dist]);
+                }
+            }
+        }
+    }
+
+    let adler = &zlib[bit_pos.div_ceil(8)..];
+    assert_eq!(adler, adler32(&data).to_be_bytes());
+    data
+}
+
+#[test]
+fn encode() {
+    let rgb: Vec<u8> = (0..2 * 2 * 3).collect();
+    let png = png_encode(&rgb, 2, 2);
+    assert!(png.starts_with(PNG_SIGNATURE));
+
+    let chunks = png_chunks(&png);
+    let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+    assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+    assert_eq!(chunks[0].1, [0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+    let raw = [&[0][..], &rgb[..6], &[0], &rgb[6..]].concat();
+    assert_eq!(zlib_decompress(&chunks[1].1), raw);
+}
+
+#[test]
+#[should_panic]
+fn encode_short_frame() {
+    png_encode(&[0; 2 * 2 * 3 - 1], 2, 2);
+}
+
+#[test]
+fn compress() {
+    let mut data = vec![];
+    for idx in 0..100_000u32 {
+        data.push((idx / 300) as u8);
+        data.push(idx.wrapping_mul(idx) as u8);
+    }
+    data.extend([0xAB; 2000]);
+
+    for data in [&[][..], &[1], &[1, 2, 3], &data] {
+        assert_eq!(zlib_decompress(&zlib_compress(data)), data);
+    }
+
+    let zlib = zlib_compress(&[0x55; 10000]);
+    assert!(zlib.len() < 100);
+}
@@ -7,13 +7,14 @@ mod test;
 
 use crate::bitfield::*;
 use crate::cartridge::NAMETABLE_0_START;
-use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FRAME_SIZE_BYTES, PPU_REG_END, PPU_REG_START};
+use crate::palette::{Palette, Rgb, PALETTE_SIZE};
+use crate::region::Region;
+use crate::video::VideoSink;
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, PPU_REG_END, PPU_REG_START};
 use ppu_palette::*;
 use ppu_regs::*;
 
-pub const NUM_ROWS: u32 = 262;
 pub const NUM_COLS: u32 = 341;
-pub const CYCLES_PER_FRAME: u32 = NUM_COLS * NUM_ROWS;
 const PPU_CTRL: u16 = 0;
 const PPU_MASK: u16 = 1;
 const PPU_STATUS: u16 = 2;
@@ -31,6 +32,17 @@ const ATTR_TABLE_OFFSET: u32 = 0x23C0;
 const PALETTE_START: u16 = 0x3F00;
 pub const OAM_SIZE: usize = 256;
 const SPRITES_PER_ROW: usize = 8;
+const SECONDARY_OAM_SIZE: usize = SPRITES_PER_ROW * 4;
+const SPRITE_EVAL_START: u32 = 65;
+const OAM_ATTR_MASK: u8 = 0xE3;
+const GREYSCALE_MASK: usize = 0x30;
+/// Bits of PPUSTATUS that are driven on reads, the rest are open bus.
+const STATUS_MASK: u8 = 0xE0;
+/// Palette RAM is 6 bits wide, reads get the top 2 bits from open bus.
+const PALETTE_DATA_MASK: u8 = 0x3F;
+/// Bits of the I/O latch decay to 0 around 600 ms after they were last
+/// driven.
+const OPEN_BUS_DECAY_CYCLES: u32 = 3_200_000;
 
 #[derive(Copy, Clone)]
 struct SpriteInfo {
@@ -43,6 +55,16 @@ struct SpriteInfo {
     sprite_0: bool,
 }
 
+/// Sprite evaluation copies in-range sprites to secondary OAM until it is
+/// full, then looks for overflow, then idles until the end of the row.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+enum SpriteEval {
+    #[default]
+    Copy,
+    Overflow,
+    Done,
+}
+
 impl Default for SpriteInfo {
     fn default() -> Self {
         Self {
@@ -66,8 +88,12 @@ pub struct Ppu {
     w: bool,
     x: u8,
     read_buf: u8,
+    open_bus: u8,
+    open_bus_refreshed: [u32; 8],
     palette_ram: [u8; PALETTE_RAM_SIZE],
     nmi: bool,
+    vblank_suppressed: bool,
+    nmi_suppressed: bool,
     bg_patt_shift_reg_0: u16,
     bg_patt_shift_reg_1: u16,
     bg_attr_shift_reg_0: u16,
@@ -77,13 +103,22 @@ pub struct Ppu {
     bg_pattern_0: u8,
     bg_pattern_1: u8,
     oam: [u8; OAM_SIZE],
+    secondary_oam: [u8; SECONDARY_OAM_SIZE],
+    secondary_oam_idx: usize,
+    oam_bus: u8,
+    sprite_eval: SpriteEval,
+    sprite_copy_bytes: u8,
+    sprite_0_next: bool,
     sprite_infos: [SpriteInfo; SPRITES_PER_ROW],
     sprite_patterns_0: [u8; SPRITES_PER_ROW],
     sprite_patterns_1: [u8; SPRITES_PER_ROW],
     oam_addr: u8,
     cycles: u32,
+    frames: u64,
     row: u32,
     col: u32,
+    region: Region,
+    palette: Palette,
 }
 
 impl Default for Ppu {
@@ -97,8 +132,12 @@ impl Default for Ppu {
             w: Default::default(),
             x: Default::default(),
             read_buf: Default::default(),
+            open_bus: Default::default(),
+            open_bus_refreshed: Default::default(),
             palette_ram: Default::default(),
             nmi: Default::default(),
+            vblank_suppressed: false,
+            nmi_suppressed: false,
             bg_patt_shift_reg_0: Default::default(),
             bg_patt_shift_reg_1: Default::default(),
             bg_attr_shift_reg_0: Default::default(),
@@ -108,13 +147,22 @@ impl Default for Ppu {
             bg_pattern_0: Default::default(),
             bg_pattern_1: Default::default(),
             oam: [0xFF; OAM_SIZE],
+            secondary_oam: [0xFF; SECONDARY_OAM_SIZE],
+            secondary_oam_idx: 0,
+            oam_bus: 0xFF,
+            sprite_eval: SpriteEval::default(),
+            sprite_copy_bytes: 0,
+            sprite_0_next: false,
             sprite_infos: [SpriteInfo::default(); SPRITES_PER_ROW],
             sprite_patterns_0: [0; SPRITES_PER_ROW],
             sprite_patterns_1: [0; SPRITES_PER_ROW],
             oam_addr: Default::default(),
             cycles: Default::default(),
+            frames: 0,
             row: 0,
             col: 0,
+            region: Region::Ntsc,
+            palette: *PPU_PALETTE_FULL,
         }
     }
 }
@@ -125,8 +173,21 @@ pub trait PpuBus {
 }
 
 impl Ppu {
-    pub fn tick(&mut self, bus: &mut impl PpuBus, frame: &mut [u8; FRAME_SIZE_BYTES]) {
-        if (self.is_visible_row() || self.is_prerender_row()) && self.rendering_enabled() {
+    pub fn init(region: Region) -> Self {
+        Self {
+            region,
+            ..Default::default()
+        }
+    }
+
+    pub fn tick(&mut self, bus: &mut impl PpuBus, frame: &mut impl VideoSink) {
+        if self.is_rendering() {
+            if self.is_prerender_row() && self.col == 1 && self.oam_addr >= 8 {
+                self.corrupt_oam();
+            }
+
+            self.sprite_eval_tick();
+
             if self.is_visible_col() || self.is_bg_prefetch_col() {
                 self.update_shift_regs();
 
@@ -153,6 +214,10 @@ impl Ppu {
                 self.v.set_nx(self.t.nx());
             }
 
+            if (DISPLAY_WIDTH + 1..=SPRITE_FETCH_END).contains(&self.col) {
+                self.oam_addr = 0;
+            }
+
             if self.is_sprite_fetch_col() {
                 let sprite_idx = ((self.col - SPRITE_FETCH_START) / 8) as usize;
                 match (self.col - SPRITE_FETCH_START) % 8 {
@@ -166,22 +231,26 @@ impl Ppu {
         if self.col == DISPLAY_WIDTH + 1 {
             self.sprite_infos.fill(SpriteInfo::default());
 
-            if self.row < DISPLAY_HEIGHT {
-                self.sprite_eval();
+            if self.is_visible_row() && self.rendering_enabled() {
+                self.load_sprite_infos();
             }
         }
 
-        if self.row == DISPLAY_HEIGHT + 1 && self.col == 1 {
-            self.status.set_v(1)
+        if self.row == self.region.vblank_row() && self.col == 1 {
+            if !self.vblank_suppressed {
+                self.status.set_v(1)
+            }
+            self.vblank_suppressed = false;
         }
 
-        if self.row == NUM_ROWS - 1 && self.col == 1 {
+        if self.is_prerender_row() && self.col == 1 {
+            self.nmi_suppressed = false;
             self.status.set_v(0);
             self.status.set_s(0);
             self.status.set_o(0);
         }
 
-        if self.row == NUM_ROWS - 1 && self.rendering_enabled() {
+        if self.is_prerender_row() && self.rendering_enabled() {
             self.v.set_coarse_y(self.t.coarse_y());
             self.v.set_fine_y(self.t.fine_y());
             self.v.set_ny(self.t.ny());
@@ -192,42 +261,87 @@ impl Ppu {
         }
 
         self.col = (self.col + 1) % NUM_COLS;
+
+        // The last cycle of the pre-render row is skipped on odd frames.
+        if self.is_prerender_row()
+            && self.col == NUM_COLS - 1
+            && self.frames % 2 == 1
+            && self.rendering_enabled()
+            && self.region.skips_odd_frame_cycle()
+        {
+            self.col = 0;
+        }
+
         if self.col == 0 {
-            self.row = (self.row + 1) % NUM_ROWS;
+            self.row = (self.row + 1) % self.region.num_rows();
+            if self.row == 0 {
+                self.frames += 1;
+            }
         }
-        self.nmi = ((self.status.v() & self.ctrl.v()) == 1);
+        self.update_nmi();
         self.cycles += 1;
     }
 
     pub fn cpu_read(&mut self, addr: u16, bus: &mut impl PpuBus) -> u8 {
-        match addr % 8 {
+        let open_bus = self.open_bus();
+
+        // Each read drives some bits of the I/O latch, the rest come from
+        // whatever was last on it.
+        let (val, mask) = match addr % 8 {
             PPU_STATUS => {
-                let val = self.status.data;
+                // Reading just before vblank starts stops the flag being set
+                // at all, reading just after still sees it but stops the NMI.
+                if self.row == self.region.vblank_row() {
+                    match self.col {
+                        1 => self.vblank_suppressed = true,
+                        2 | 3 => self.nmi_suppressed = true,
+                        _ => (),
+                    }
+                }
+
+                let val = (self.status.data & STATUS_MASK) | (open_bus & !STATUS_MASK);
                 self.status.set_v(0);
                 self.w = false;
-                val
+                self.update_nmi();
+                (val, STATUS_MASK)
             }
-            OAM_DATA => self.oam[self.oam_addr as usize],
+            OAM_DATA if self.is_rendering() => (self.oam_bus, 0xFF),
+            OAM_DATA => (self.oam[self.oam_addr as usize], 0xFF),
             PPU_DATA => {
-                if self.v.addr() >= PALETTE_START {
-                    self.read_buf = self.palette_ram[get_palette_addr(self.v.addr())];
-                    self.read_buf
+                let addr = self.v.addr();
+                self.v.data += if self.ctrl.i() == 1 { 32 } else { 1 };
+
+                if addr >= PALETTE_START {
+                    // The buffer is filled from the nametable "underneath"
+                    // the palette instead.
+                    self.read_buf = bus.ppu_read(addr - 0x1000);
+
+                    let mut colour = self.palette_ram[get_palette_addr(addr)] & PALETTE_DATA_MASK;
+                    if self.mask.greyscale() == 1 {
+                        colour &= GREYSCALE_MASK as u8;
+                    }
+                    ((open_bus & !PALETTE_DATA_MASK) | colour, PALETTE_DATA_MASK)
                 } else {
                     let val = self.read_buf;
-                    self.read_buf = bus.ppu_read(self.v.addr());
-                    self.v.data += if self.ctrl.i() == 1 { 32 } else { 1 };
-                    val
+                    self.read_buf = bus.ppu_read(addr);
+                    (val, 0xFF)
                 }
             }
-            _ => 0x0,
-        }
+            _ => (open_bus, 0x00),
+        };
+
+        self.refresh_open_bus(val, mask);
+        val
     }
 
     pub fn cpu_write(&mut self, addr: u16, data: u8, bus: &mut impl PpuBus) {
+        self.refresh_open_bus(data, 0xFF);
+
         match addr % 8 {
             PPU_CTRL => {
                 self.ctrl.data = data;
                 self.t.set_n(field!(data, 0, 2) as u16);
+                self.update_nmi();
             }
             PPU_MASK => {
                 self.mask.data = data;
@@ -235,9 +349,18 @@ impl Ppu {
             OAM_ADDR => {
                 self.oam_addr = data;
             }
+            // Writes during rendering are ignored, but bump OAMADDR to the
+            // next sprite.
+            OAM_DATA if self.is_rendering() => {
+                self.oam_addr = self.oam_addr.wrapping_add(4);
+            }
             OAM_DATA => {
-                self.oam[self.oam_addr as usize] = data;
-                self.oam_addr += 1;
+                self.oam[self.oam_addr as usize] = if self.oam_addr % 4 == 2 {
+                    data & OAM_ATTR_MASK
+                } else {
+                    data
+                };
+                self.oam_addr = self.oam_addr.wrapping_add(1);
             }
             PPU_SCROLL => {
                 if !self.w {
@@ -273,10 +396,59 @@ impl Ppu {
         }
     }
 
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.palette = *palette;
+    }
+
+    /// What the reset button clears: PPUCTRL, PPUMASK, the fine X scroll,
+    /// the write toggle and the PPUDATA read buffer. VRAM, OAM and the
+    /// palette are kept, as is the frame timing.
+    pub fn reset(&mut self) {
+        self.ctrl.data = 0;
+        self.mask.data = 0;
+        self.x = 0;
+        self.w = false;
+        self.read_buf = 0;
+        self.update_nmi();
+    }
+
+    /// The NMI output follows the vblank flag and PPUCTRL straight away, so
+    /// toggling NMIs on during vblank can trigger more than one.
+    fn update_nmi(&mut self) {
+        self.nmi = (self.status.v() & self.ctrl.v()) == 1 && !self.nmi_suppressed;
+    }
+
+    /// The I/O latch, with any bits that haven't been driven recently
+    /// decayed to 0.
+    fn open_bus(&mut self) -> u8 {
+        for (bit, &refreshed) in self.open_bus_refreshed.iter().enumerate() {
+            if self.cycles.wrapping_sub(refreshed) > OPEN_BUS_DECAY_CYCLES {
+                self.open_bus &= !(1 << bit);
+            }
+        }
+
+        self.open_bus
+    }
+
+    fn refresh_open_bus(&mut self, val: u8, mask: u8) {
+        self.open_bus = (self.open_bus & !mask) | (val & mask);
+
+        for (bit, refreshed) in self.open_bus_refreshed.iter_mut().enumerate() {
+            if mask & (1 << bit) != 0 {
+                *refreshed = self.cycles;
+            }
+        }
+    }
+
     pub fn cycles(&self) -> u32 {
         self.cycles
     }
 
+    /// Number of frames started, the first being frame 0.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
     pub fn nmi(&self) -> bool {
         self.nmi
     }
@@ -389,64 +561,176 @@ impl Ppu {
         palette_addr
     }
 
-    fn sprite_eval(&mut self) {
-        let mut oam_idx = 0;
-        let mut sprites_found = 0;
-
-        while oam_idx < OAM_SIZE {
-            let y = self.oam[oam_idx];
+    /// Runs one cycle of sprite evaluation for the next row. Secondary OAM is
+    /// cleared over cycles 1-64, then from cycle 65 OAM is read on odd cycles
+    /// and the value handled on even cycles, starting at OAMADDR. During
+    /// sprite fetches secondary OAM is read back out.
+    fn sprite_eval_tick(&mut self) {
+        match self.col {
+            1..=64 if self.is_visible_row() => {
+                self.oam_bus = 0xFF;
+                if self.col & 0x1 == 0 {
+                    self.secondary_oam[((self.col - 1) / 2) as usize] = 0xFF;
+                }
+            }
+            SPRITE_EVAL_START..=DISPLAY_WIDTH if self.is_visible_row() => {
+                if self.col == SPRITE_EVAL_START {
+                    self.sprite_eval = SpriteEval::Copy;
+                    self.secondary_oam_idx = 0;
+                    self.sprite_copy_bytes = 0;
+                    self.sprite_0_next = false;
+                }
 
-            let mut fine_y = self.row - (y as u32);
-            let y_max = if self.sprites_8x16() { 15 } else { 7 };
+                if self.col % 2 == 1 {
+                    self.oam_bus = self.oam[self.oam_addr as usize];
+                } else {
+                    self.sprite_eval_step();
+                }
+            }
+            SPRITE_FETCH_START..=SPRITE_FETCH_END => {
+                let slot = ((self.col - SPRITE_FETCH_START) / 8) as usize;
+                let byte = ((self.col - SPRITE_FETCH_START) % 8).min(3) as usize;
+                self.oam_bus = self.secondary_oam[(slot * 4) + byte];
+            }
+            BG_PRE_FETCH_START.. => self.oam_bus = self.secondary_oam[0],
+            _ => (),
+        }
+    }
 
-            if (0..=y_max).contains(&fine_y) {
-                if sprites_found < SPRITES_PER_ROW {
-                    let mut tile = if !self.sprites_8x16() {
-                        self.oam[oam_idx + 1]
-                    } else {
-                        self.oam[oam_idx + 1] & !0x1
-                    };
+    fn sprite_eval_step(&mut self) {
+        let data = self.oam_bus;
 
-                    let pattern_table = if !self.sprites_8x16() {
-                        self.ctrl.s()
-                    } else {
-                        self.oam[oam_idx + 1] & 0x1
-                    } as u16;
+        match self.sprite_eval {
+            SpriteEval::Copy => {
+                self.secondary_oam[self.secondary_oam_idx] = data;
 
-                    let attr = SpriteAttr {
-                        data: self.oam[oam_idx + 2],
-                    };
+                if self.sprite_copy_bytes > 0 {
+                    self.sprite_copy_bytes -= 1;
+                    self.secondary_oam_idx += 1;
+                    self.sprite_eval_inc_oam_addr(1);
 
-                    let x = self.oam[oam_idx + 3];
+                    if self.sprite_copy_bytes == 0
+                        && self.secondary_oam_idx == SECONDARY_OAM_SIZE
+                        && self.sprite_eval == SpriteEval::Copy
+                    {
+                        self.sprite_eval = SpriteEval::Overflow;
+                    }
+                } else if self.sprite_in_range(data) {
+                    // Whichever sprite is checked first is treated as sprite
+                    // 0, even if OAMADDR didn't start at 0.
+                    self.sprite_0_next |= self.col == SPRITE_EVAL_START + 1;
+                    self.sprite_copy_bytes = 3;
+                    self.secondary_oam_idx += 1;
+                    self.sprite_eval_inc_oam_addr(1);
+                } else {
+                    self.sprite_eval_inc_oam_addr(4);
+                }
+            }
+            SpriteEval::Overflow => {
+                if self.sprite_copy_bytes > 0 {
+                    self.sprite_copy_bytes -= 1;
+                    self.sprite_eval_inc_oam_addr(1);
 
-                    if attr.flip_ver() == 1 {
-                        fine_y = y_max - fine_y;
+                    if self.sprite_copy_bytes == 0 {
+                        self.sprite_eval = SpriteEval::Done;
                     }
-                    if fine_y > 7 {
-                        tile += 1;
+                } else if self.sprite_in_range(data) {
+                    self.status.set_o(1);
+                    self.sprite_copy_bytes = 3;
+                    self.sprite_eval_inc_oam_addr(1);
+                } else {
+                    // The hardware bug: moving on to the next sprite also
+                    // moves on a byte within it, so tile numbers, attributes
+                    // and X positions get checked as Y positions.
+                    let wrapped = self.oam_addr >= 0xFC;
+                    self.oam_addr = (self.oam_addr.wrapping_add(4) & 0xFC)
+                        | (self.oam_addr.wrapping_add(1) & 0x03);
+
+                    if wrapped {
+                        self.sprite_eval = SpriteEval::Done;
                     }
+                }
+            }
+            SpriteEval::Done => self.oam_addr = self.oam_addr.wrapping_add(4),
+        }
+    }
+
+    /// Evaluation stops copying once it wraps past the end of OAM.
+    fn sprite_eval_inc_oam_addr(&mut self, amt: u8) {
+        let (addr, wrapped) = self.oam_addr.overflowing_add(amt);
+        self.oam_addr = addr;
+
+        if wrapped {
+            self.sprite_eval = SpriteEval::Done;
+        }
+    }
+
+    fn sprite_in_range(&self, y: u8) -> bool {
+        let height = if self.sprites_8x16() { 16 } else { 8 };
+        self.row.wrapping_sub(y as u32) < height
+    }
 
-                    self.sprite_infos[sprites_found] = SpriteInfo {
-                        x_pos: x,
-                        y_pos: y,
-                        fine_y: fine_y as u8,
-                        pattern_table: pattern_table as u8,
-                        tile: tile,
-                        attr: attr,
-                        sprite_0: oam_idx == 0,
-                    };
-
-                    sprites_found += 1;
+    /// Decodes the sprites in secondary OAM for fetching. Unused slots hold
+    /// $FF, which is never in range, and fetch tile $FF.
+    fn load_sprite_infos(&mut self) {
+        for idx in 0..SPRITES_PER_ROW {
+            let sprite = &self.secondary_oam[idx * 4..(idx + 1) * 4];
+            let y = sprite[0];
+
+            if !self.sprite_in_range(y) {
+                self.sprite_infos[idx].pattern_table = if !self.sprites_8x16() {
+                    self.ctrl.s()
                 } else {
-                    self.status.set_o(1);
-                    break;
-                }
+                    1
+                };
+                continue;
             }
 
-            oam_idx += 4;
+            let mut fine_y = self.row - (y as u32);
+            let y_max = if self.sprites_8x16() { 15 } else { 7 };
+
+            let mut tile = if !self.sprites_8x16() {
+                sprite[1]
+            } else {
+                sprite[1] & !0x1
+            };
+
+            let pattern_table = if !self.sprites_8x16() {
+                self.ctrl.s()
+            } else {
+                sprite[1] & 0x1
+            };
+
+            let attr = SpriteAttr { data: sprite[2] };
+
+            let x = sprite[3];
+
+            if attr.flip_ver() == 1 {
+                fine_y = y_max - fine_y;
+            }
+            if fine_y > 7 {
+                tile += 1;
+            }
+
+            self.sprite_infos[idx] = SpriteInfo {
+                x_pos: x,
+                y_pos: y,
+                fine_y: fine_y as u8,
+                pattern_table,
+                tile,
+                attr,
+                sprite_0: idx == 0 && self.sprite_0_next,
+            };
         }
     }
 
+    /// If OAMADDR isn't at the first two sprites when rendering starts, the
+    /// row of OAM it points into is copied over them.
+    fn corrupt_oam(&mut self) {
+        let start = (self.oam_addr & 0xF8) as usize;
+        self.oam.copy_within(start..start + 8, 0);
+    }
+
     fn fetch_sprite_pattern(&mut self, bus: &mut impl PpuBus, plane: u16, sprite_idx: usize) {
         let sprite_info = self.sprite_infos[sprite_idx];
         let mut pattern_addr = PatternAddr::default();
@@ -490,14 +774,13 @@ impl Ppu {
         (SpriteInfo::default(), PaletteAddr { data: 0 })
     }
 
-    fn draw_pixel(&mut self, frame: &mut [u8; FRAME_SIZE_BYTES]) {
+    fn draw_pixel(&mut self, frame: &mut impl VideoSink) {
         let (x, y) = (self.col - 1, self.row);
-        let frame_idx = (((y * DISPLAY_WIDTH) + x) * 3) as usize;
         let bg_addr = self.get_bg_pixel_info();
         let (sprite_info, sprite_addr) = self.get_sprite_pixel_info();
-        let transparent_pixel = self.get_color(PaletteAddr { data: 0 });
-        let mut bg_pixel = self.get_color(bg_addr);
-        let mut sprite_pixel = self.get_color(sprite_addr);
+        let transparent_pixel = self.pixel_value(PaletteAddr { data: 0 });
+        let mut bg_pixel = self.pixel_value(bg_addr);
+        let mut sprite_pixel = self.pixel_value(sprite_addr);
 
         if x < 8 {
             if self.mask.bg_left_show() != 1 {
@@ -516,7 +799,7 @@ impl Ppu {
             self.status.set_s(1)
         }
 
-        Rgb(frame[frame_idx], frame[frame_idx + 1], frame[frame_idx + 2]) = match (
+        let pixel = match (
             sprite_info.attr.priority() == 0,
             is_opaque(sprite_addr) && self.sprites_enabled(),
             is_opaque(bg_addr) && self.bg_enabled(),
@@ -528,11 +811,33 @@ impl Ppu {
             (true, false, false) => transparent_pixel,
             (true, false, true) => bg_pixel,
             (true, true, _) => sprite_pixel,
+        };
+
+        frame.put_pixel(x, y, pixel, self.palette[pixel as usize]);
+    }
+
+    /// The 9-bit value output for a pixel, the colour from palette RAM with
+    /// the emphasis bits above it.
+    fn pixel_value(&self, palette_addr: PaletteAddr) -> u16 {
+        let mut colour =
+            self.palette_ram[get_palette_addr(palette_addr.data)] as usize % PALETTE_SIZE;
+        if self.mask.greyscale() == 1 {
+            colour &= GREYSCALE_MASK;
         }
+
+        ((self.emphasis() << 6) | colour) as u16
     }
 
-    fn get_color(&self, palette_addr: PaletteAddr) -> Rgb {
-        PPU_PALETTE[self.palette_ram[get_palette_addr(palette_addr.data)] as usize % PALETTE_SIZE]
+    /// The 2C07 and Dendy PPUs swap the red and green emphasis bits.
+    fn emphasis(&self) -> usize {
+        let emphasis = self.mask.emphasis() as usize;
+
+        match self.region {
+            Region::Ntsc => emphasis,
+            Region::Pal | Region::Dendy => {
+                (emphasis & 0x4) | ((emphasis & 0x1) << 1) | ((emphasis & 0x2) >> 1)
+            }
+        }
     }
 
     fn fine_x(&self, val: u16) -> u16 {
@@ -551,6 +856,10 @@ impl Ppu {
         self.bg_enabled() || self.sprites_enabled()
     }
 
+    fn is_rendering(&self) -> bool {
+        (self.is_visible_row() || self.is_prerender_row()) && self.rendering_enabled()
+    }
+
     fn sprites_8x16(&self) -> bool {
         self.ctrl.h() == 1
     }
@@ -560,7 +869,7 @@ impl Ppu {
     }
 
     fn is_prerender_row(&self) -> bool {
-        self.row == NUM_ROWS - 1
+        self.row == self.region.num_rows() - 1
     }
 
     fn is_visible_col(&self) -> bool {
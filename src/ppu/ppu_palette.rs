@@ -1,7 +1,10 @@
-pub const PALETTE_SIZE: usize = 64;
+use crate::palette::{emphasis_palette, Palette, Rgb, PALETTE_SIZE};
+use lazy_static::lazy_static;
 
-#[derive(Clone, Copy, PartialEq)]
-pub struct Rgb(pub u8, pub u8, pub u8);
+lazy_static! {
+    /// `PPU_PALETTE` followed by its 7 emphasised variants.
+    pub static ref PPU_PALETTE_FULL: Palette = emphasis_palette(&PPU_PALETTE);
+}
 
 pub static PPU_PALETTE: [Rgb; PALETTE_SIZE] = [
     Rgb(84, 84, 84),
@@ -42,10 +42,12 @@ pub struct PpuMask {
 }
 
 impl PpuMask {
+    get_set_field!(greyscale, set_greyscale, 0, 1, u8);
     get_set_field!(bg_left_show, set_bg_left_show, 1, 1, u8);
     get_set_field!(sprite_left_show, set_sprite_left_show, 2, 1, u8);
     get_set_field!(bg_enabled, set_bg_enabled, 3, 1, u8);
     get_set_field!(sprites_enabled, set_sprites_enabled, 4, 1, u8);
+    get_set_field!(emphasis, set_emphasis, 5, 3, u8);
 }
 
 #[derive(Copy, Clone, Default)]
@@ -1,5 +1,6 @@
 use super::ppu_regs::*;
 use super::*;
+use crate::{FRAME_SIZE_BYTES, FRAME_SIZE_PIXELS};
 
 struct TestPpuBus {
     mem: [u8; 0x4000],
@@ -69,3 +70,257 @@ fn reg_write() {
     assert_eq!(ppu.t.addr_low(), 0xF0);
     assert_eq!(ppu.v.data, 0x3DF0);
 }
+
+#[test]
+fn region_vblank() {
+    for region in [Region::Ntsc, Region::Pal, Region::Dendy] {
+        let mut ppu = Ppu::init(region);
+        let mut bus = TestPpuBus::default();
+        let mut frame = [0; FRAME_SIZE_BYTES];
+
+        while ppu.status.v() == 0 {
+            ppu.tick(&mut bus, &mut frame);
+        }
+        assert_eq!(ppu.row, region.vblank_row());
+
+        while ppu.status.v() == 1 {
+            ppu.tick(&mut bus, &mut frame);
+        }
+        assert_eq!(ppu.row, region.num_rows() - 1);
+    }
+}
+
+#[test]
+fn greyscale_and_emphasis() {
+    let (mut ppu, mut bus) = init();
+    let backdrop = PaletteAddr { data: 0 };
+    ppu.palette_ram[0] = 0x16;
+
+    ppu.cpu_write(0x2001, 0x01, &mut bus);
+    assert!(ppu.palette[ppu.pixel_value(backdrop) as usize] == PPU_PALETTE[0x10]);
+
+    ppu.cpu_write(0x2001, 0x20, &mut bus);
+    let Rgb(r, g, b) = ppu.palette[ppu.pixel_value(backdrop) as usize];
+    let Rgb(r_base, g_base, b_base) = PPU_PALETTE[0x16];
+    assert_eq!(r, r_base);
+    assert!(g < g_base || g_base == 0);
+    assert!(b < b_base || b_base == 0);
+
+    let mut ppu = Ppu::init(Region::Pal);
+    ppu.palette_ram[0] = 0x16;
+    ppu.cpu_write(0x2001, 0x40, &mut bus);
+    assert_eq!(ppu.palette[ppu.pixel_value(backdrop) as usize].0, r_base);
+}
+
+#[test]
+fn pixel_values() {
+    let (mut ppu, mut bus) = init();
+    let mut indices = [0; FRAME_SIZE_PIXELS];
+    let mut rgb = [0; FRAME_SIZE_BYTES];
+    ppu.palette_ram[0] = 0x21;
+    ppu.cpu_write(0x2001, 0xA0, &mut bus);
+
+    for _ in 0..NUM_COLS {
+        ppu.tick(&mut bus, &mut indices);
+    }
+    assert!(indices[..DISPLAY_WIDTH as usize]
+        .iter()
+        .all(|&pixel| pixel == 0x161));
+
+    let mut ppu = Ppu::default();
+    ppu.palette_ram[0] = 0x21;
+    ppu.cpu_write(0x2001, 0xA0, &mut bus);
+    for _ in 0..NUM_COLS {
+        ppu.tick(&mut bus, &mut rgb);
+    }
+    let Rgb(r, g, b) = ppu.palette[0x161];
+    assert_eq!(rgb[..3], [r, g, b]);
+}
+
+fn run_to(ppu: &mut Ppu, bus: &mut TestPpuBus, row: u32, col: u32) {
+    let mut indices = [0; FRAME_SIZE_PIXELS];
+
+    while ppu.row != row || ppu.col != col {
+        ppu.tick(bus, &mut indices);
+    }
+}
+
+fn init_oam(ppu: &mut Ppu, bus: &mut TestPpuBus, sprites: &[(usize, [u8; 4])]) {
+    ppu.oam = [0xF0; OAM_SIZE];
+    for (idx, sprite) in sprites {
+        ppu.oam[idx * 4..(idx + 1) * 4].copy_from_slice(sprite);
+    }
+    ppu.cpu_write(0x2001, 0x18, bus);
+}
+
+#[test]
+fn sprite_overflow() {
+    let (mut ppu, mut bus) = init();
+    let mut sprites: Vec<_> = (0..8).map(|idx| (idx, [10, 0, 0, 0])).collect();
+    sprites.push((9, [0xF0, 10, 0xF0, 0xF0]));
+    init_oam(&mut ppu, &mut bus, &sprites);
+
+    run_to(&mut ppu, &mut bus, 10, DISPLAY_WIDTH + 1);
+    assert_eq!(ppu.status.o(), 1);
+    assert!(ppu.secondary_oam.iter().step_by(4).all(|&y| y == 10));
+
+    let (mut ppu, mut bus) = init();
+    let mut sprites: Vec<_> = (0..8).map(|idx| (idx, [10, 0, 0, 0])).collect();
+    sprites.push((9, [10, 0xF0, 0xF0, 0xF0]));
+    init_oam(&mut ppu, &mut bus, &sprites);
+
+    run_to(&mut ppu, &mut bus, 10, DISPLAY_WIDTH + 1);
+    assert_eq!(ppu.status.o(), 0);
+}
+
+#[test]
+fn sprite_eval_start() {
+    let (mut ppu, mut bus) = init();
+    init_oam(
+        &mut ppu,
+        &mut bus,
+        &[(0, [0, 0x41, 0, 0]), (1, [0, 0x42, 0, 0])],
+    );
+    ppu.cpu_write(0x2003, 4, &mut bus);
+
+    run_to(&mut ppu, &mut bus, 0, DISPLAY_WIDTH + 2);
+    assert_eq!(ppu.secondary_oam[1], 0x42);
+    assert!(ppu.sprite_infos[0].sprite_0);
+    assert_eq!(ppu.oam_addr, 0);
+}
+
+#[test]
+fn oam_data_read() {
+    let (mut ppu, mut bus) = init();
+    ppu.cpu_write(0x2003, 2, &mut bus);
+    ppu.cpu_write(0x2004, 0xFF, &mut bus);
+    ppu.cpu_write(0x2003, 2, &mut bus);
+    assert_eq!(ppu.cpu_read(0x2004, &mut bus), 0xE3);
+
+    init_oam(&mut ppu, &mut bus, &[(0, [10, 0x41, 0, 0])]);
+    run_to(&mut ppu, &mut bus, 10, 30);
+    assert_eq!(ppu.cpu_read(0x2004, &mut bus), 0xFF);
+
+    run_to(&mut ppu, &mut bus, 10, DISPLAY_WIDTH + 4);
+    assert_eq!(ppu.cpu_read(0x2004, &mut bus), 0x41);
+
+    ppu.cpu_write(0x2004, 0x12, &mut bus);
+    assert_eq!(ppu.oam_addr, 4);
+    assert_eq!(ppu.oam[0], 10);
+}
+
+#[test]
+fn oam_addr_corruption() {
+    let (mut ppu, mut bus) = init();
+    init_oam(&mut ppu, &mut bus, &[(4, [1, 2, 3, 4]), (5, [5, 6, 7, 8])]);
+    run_to(&mut ppu, &mut bus, DISPLAY_HEIGHT + 1, 0);
+    ppu.cpu_write(0x2003, 0x13, &mut bus);
+
+    let prerender_row = ppu.region.num_rows() - 1;
+    run_to(&mut ppu, &mut bus, prerender_row, 2);
+    assert_eq!(ppu.oam[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn open_bus() {
+    let (mut ppu, mut bus) = init();
+
+    ppu.cpu_write(0x2000, 0x5A, &mut bus);
+    ppu.cpu_write(0x2000, 0x00, &mut bus);
+    ppu.cpu_write(0x2003, 0x5A, &mut bus);
+    assert_eq!(ppu.cpu_read(0x2005, &mut bus), 0x5A);
+    ppu.status.set_v(1);
+    assert_eq!(ppu.cpu_read(0x2002, &mut bus), 0x9A);
+
+    ppu.cycles += OPEN_BUS_DECAY_CYCLES / 2;
+    ppu.status.set_v(1);
+    ppu.cpu_read(0x2002, &mut bus);
+    ppu.cycles += (OPEN_BUS_DECAY_CYCLES / 2) + 1;
+    assert_eq!(ppu.cpu_read(0x2000, &mut bus), 0x80);
+}
+
+#[test]
+fn palette_read() {
+    let (mut ppu, mut bus) = init();
+    ppu.palette_ram[1] = 0x2A;
+    bus.mem[0x2FC1] = 0x77;
+
+    ppu.cpu_write(0x2006, 0x3F, &mut bus);
+    ppu.cpu_write(0x2006, 0xC1, &mut bus);
+    ppu.cpu_write(0x2001, 0xC0, &mut bus);
+    assert_eq!(ppu.cpu_read(0x2007, &mut bus), 0xEA);
+    assert_eq!(ppu.read_buf, 0x77);
+    assert_eq!(ppu.v.addr(), 0x3FC2);
+}
+
+#[test]
+fn vblank_suppression() {
+    let (mut ppu, mut bus) = init();
+    let vblank_row = ppu.region.vblank_row();
+    ppu.cpu_write(0x2000, 0x80, &mut bus);
+
+    run_to(&mut ppu, &mut bus, vblank_row, 1);
+    assert_eq!(ppu.cpu_read(0x2002, &mut bus) & 0x80, 0);
+    run_to(&mut ppu, &mut bus, vblank_row, 10);
+    assert_eq!(ppu.status.v(), 0);
+    assert!(!ppu.nmi());
+
+    run_to(&mut ppu, &mut bus, vblank_row, 2);
+    assert_eq!(ppu.cpu_read(0x2002, &mut bus) & 0x80, 0x80);
+    ppu.cpu_write(0x2000, 0x00, &mut bus);
+    ppu.cpu_write(0x2000, 0x80, &mut bus);
+    assert!(!ppu.nmi());
+}
+
+#[test]
+fn nmi_toggle() {
+    let (mut ppu, mut bus) = init();
+    let vblank_row = ppu.region.vblank_row();
+    run_to(&mut ppu, &mut bus, vblank_row + 1, 0);
+    assert!(!ppu.nmi());
+
+    ppu.cpu_write(0x2000, 0x80, &mut bus);
+    assert!(ppu.nmi());
+    ppu.cpu_write(0x2000, 0x00, &mut bus);
+    assert!(!ppu.nmi());
+}
+
+#[test]
+fn reset() {
+    let (mut ppu, mut bus) = init();
+    let vblank_row = ppu.region.vblank_row();
+    run_to(&mut ppu, &mut bus, vblank_row + 1, 0);
+    ppu.cpu_write(0x2000, 0x80, &mut bus);
+    ppu.cpu_write(0x2001, 0x1E, &mut bus);
+    ppu.cpu_write(0x2005, 0x7D, &mut bus);
+    assert!(ppu.nmi());
+
+    ppu.reset();
+    assert_eq!(ppu.ctrl.data, 0);
+    assert_eq!(ppu.mask.data, 0);
+    assert_eq!(ppu.x, 0);
+    assert!(!ppu.w);
+    assert!(!ppu.nmi());
+    assert!(!ppu.is_rendering());
+}
+
+#[test]
+fn odd_frame_skip() {
+    for (region, skip) in [(Region::Ntsc, 1), (Region::Pal, 0)] {
+        let mut ppu = Ppu::init(region);
+        let mut bus = TestPpuBus::default();
+        let mut indices = [0; FRAME_SIZE_PIXELS];
+        ppu.cpu_write(0x2001, 0x08, &mut bus);
+
+        let mut frame_cycles = |ppu: &mut Ppu| {
+            let (start, frame) = (ppu.cycles(), ppu.frames());
+            while ppu.frames() == frame {
+                ppu.tick(&mut bus, &mut indices);
+            }
+            ppu.cycles() - start
+        };
+
+        assert_eq!(frame_cycles(&mut ppu), region.cycles_per_frame());
+        assert_eq!(frame_cycles(&mut ppu), region.cycles_per_frame() - skip);
+    }
+}
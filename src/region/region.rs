@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod test;
+
+use crate::ppu::NUM_COLS;
+use crate::rom::Timing;
+
+const NTSC_MASTER_CLOCK_HZ: u64 = 21_477_272;
+const PAL_MASTER_CLOCK_HZ: u64 = 26_601_712;
+/// The exact master clocks, 236.25 / 11 MHz and 26.6017125 MHz, as fractions.
+const NTSC_MASTER_CLOCK_RATIO: (u64, u64) = (236_250_000, 11);
+const PAL_MASTER_CLOCK_RATIO: (u64, u64) = (53_203_425, 2);
+
+/// The console variant being emulated. Each region runs its PPU and CPU off
+/// a master clock with different dividers, and PAL and Dendy consoles draw
+/// 312 scanlines a frame instead of 262.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    /// Famiclone with a PAL frame, but NTSC-like CPU speed and vblank length.
+    Dendy,
+}
+
+impl Region {
+    /// Multi-region ROMs run as NTSC.
+    pub fn from_timing(timing: Timing) -> Self {
+        match timing {
+            Timing::Ntsc | Timing::Multi => Region::Ntsc,
+            Timing::Pal => Region::Pal,
+            Timing::Dendy => Region::Dendy,
+        }
+    }
+
+    pub fn num_rows(self) -> u32 {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal | Region::Dendy => 312,
+        }
+    }
+
+    /// The scanline on which the vblank flag is set.
+    pub fn vblank_row(self) -> u32 {
+        match self {
+            Region::Ntsc | Region::Pal => 241,
+            Region::Dendy => 291,
+        }
+    }
+
+    /// Whether the pre-render row is a cycle shorter on odd frames while
+    /// rendering is enabled.
+    pub fn skips_odd_frame_cycle(self) -> bool {
+        self == Region::Ntsc
+    }
+
+    /// Length of an even frame, odd frames may be a cycle shorter.
+    pub fn cycles_per_frame(self) -> u32 {
+        NUM_COLS * self.num_rows()
+    }
+
+    pub fn master_clock_hz(self) -> u64 {
+        match self {
+            Region::Ntsc => NTSC_MASTER_CLOCK_HZ,
+            Region::Pal | Region::Dendy => PAL_MASTER_CLOCK_HZ,
+        }
+    }
+
+    /// Master clock cycles per PPU cycle.
+    pub fn ppu_divider(self) -> u64 {
+        match self {
+            Region::Ntsc => 4,
+            Region::Pal | Region::Dendy => 5,
+        }
+    }
+
+    /// Master clock cycles per CPU cycle.
+    pub fn cpu_divider(self) -> u64 {
+        match self {
+            Region::Ntsc => 12,
+            Region::Pal => 16,
+            Region::Dendy => 15,
+        }
+    }
+
+    pub fn cpu_clock_hz(self) -> u64 {
+        self.master_clock_hz() / self.cpu_divider()
+    }
+
+    /// Exact frames per second as a reduced fraction, e.g. for a video
+    /// container. NTSC skips a cycle every other frame while rendering, so
+    /// its frames are taken to be half a cycle shorter on average.
+    pub fn frame_rate(self) -> (u64, u64) {
+        let (clock_num, clock_den) = match self {
+            Region::Ntsc => NTSC_MASTER_CLOCK_RATIO,
+            Region::Pal | Region::Dendy => PAL_MASTER_CLOCK_RATIO,
+        };
+        let half_cycles = 2 * self.cycles_per_frame() as u64 - self.skips_odd_frame_cycle() as u64;
+
+        let num = clock_num * 2;
+        let den = clock_den * half_cycles * self.ppu_divider();
+        let gcd = gcd(num, den);
+        (num / gcd, den / gcd)
+    }
+
+    pub fn frame_time_us(self) -> u64 {
+        (self.cycles_per_frame() as u64 * self.ppu_divider() * 1_000_000) / self.master_clock_hz()
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
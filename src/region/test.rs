@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn clocks() {
+    assert_eq!(Region::Ntsc.cpu_clock_hz(), 1_789_772);
+    assert_eq!(Region::Pal.cpu_clock_hz(), 1_662_607);
+    assert_eq!(Region::Dendy.cpu_clock_hz(), 1_773_447);
+
+    assert_eq!(Region::Ntsc.frame_time_us(), 16639);
+    assert_eq!(Region::Pal.frame_time_us(), 19997);
+    assert_eq!(Region::Dendy.frame_time_us(), 19997);
+}
+
+#[test]
+fn frame_rate() {
+    let fps = |region: Region| {
+        let (num, den) = region.frame_rate();
+        num as f64 / den as f64
+    };
+
+    assert_eq!(Region::Ntsc.frame_rate(), (39_375_000, 655_171));
+    assert!((fps(Region::Ntsc) - 60.0988).abs() < 0.0001);
+    assert!((fps(Region::Pal) - 50.007).abs() < 0.0001);
+    assert_eq!(Region::Dendy.frame_rate(), Region::Pal.frame_rate());
+}
+
+#[test]
+fn cpu_cycles_per_frame() {
+    let cpu_cycles = |region: Region| {
+        (region.cycles_per_frame() as u64 * region.ppu_divider()) / region.cpu_divider()
+    };
+
+    assert_eq!(cpu_cycles(Region::Ntsc), 29780);
+    assert_eq!(cpu_cycles(Region::Pal), 33247);
+    assert_eq!(cpu_cycles(Region::Dendy), 35464);
+}
+
+#[test]
+fn from_timing() {
+    assert_eq!(Region::from_timing(Timing::Multi), Region::Ntsc);
+    assert_eq!(Region::from_timing(Timing::Dendy), Region::Dendy);
+}
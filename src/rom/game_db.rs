@@ -0,0 +1,205 @@
+use super::{Rom, Timing};
+use crate::hash::{crc32, crc32_update, sha1};
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+const NUM_FIELDS: usize = 9;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DbMirroring {
+    Horizontal,
+    Vertical,
+    FourScreen,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DbEntry {
+    pub crc32: u32,
+    pub sha1: Option<[u8; 20]>,
+    pub title: String,
+    pub mapper: u16,
+    pub submapper: u8,
+    /// `None` for boards where the mapper controls mirroring.
+    pub mirroring: Option<DbMirroring>,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub timing: Timing,
+}
+
+lazy_static! {
+    /// Starts empty: no entries are bundled until they can be checked against
+    /// real dumps, so it only holds what `db_load` adds.
+    static ref GAME_DB: Mutex<Vec<DbEntry>> = Mutex::new(vec![]);
+}
+
+/// Adds the entries in `text` to the database, returning how many were added.
+///
+/// One ROM per line, keyed by the CRC32 of PRG ROM + CHR ROM. The SHA-1 of the
+/// same data is checked too when given; use `-` to skip it. Lines starting
+/// with `#` are comments.
+///
+/// ```text
+/// crc32 sha1 mapper.submapper mirroring prg_ram prg_nvram chr_ram chr_nvram timing title
+/// 1A2B3C4D - 4.0 - 0 8192 0 0 N Example Game (USA)
+/// ```
+///
+/// - mirroring: `H` (horizontal), `V` (vertical), `4` (four-screen) or `-`
+///   (mapper-controlled, keep the header's)
+/// - sizes: bytes, decimal
+/// - timing: `N` (NTSC), `P` (PAL), `M` (multi-region), `D` (Dendy)
+pub fn db_load(text: &str) -> Result<usize, String> {
+    let entries = db_parse(text)?;
+    let num_entries = entries.len();
+    GAME_DB.lock().unwrap().extend(entries);
+
+    Ok(num_entries)
+}
+
+pub fn db_lookup(prg_rom: &[u8], chr_rom: &[u8]) -> Option<DbEntry> {
+    let crc = crc32_update(crc32(prg_rom), chr_rom);
+    let db = GAME_DB.lock().unwrap();
+    let mut candidates = db.iter().filter(|entry| entry.crc32 == crc).peekable();
+
+    candidates.peek()?;
+
+    let digest = sha1(&[prg_rom, chr_rom].concat());
+    candidates
+        .find(|entry| entry.sha1.is_none_or(|entry_sha1| entry_sha1 == digest))
+        .cloned()
+}
+
+/// Overrides the header fields of `rom` with its database entry, if there is
+/// one. Returns a description of what changed, or `None` if nothing did.
+pub fn db_apply(rom: &mut Rom) -> Option<String> {
+    let entry = db_lookup(&rom.prg_rom, &rom.chr_rom)?;
+    let mut changes: Vec<String> = vec![];
+
+    macro_rules! apply {
+        ($field:ident, $val:expr) => {
+            if rom.$field != $val {
+                changes.push(format!(
+                    "{}: {:?} -> {:?}",
+                    stringify!($field),
+                    rom.$field,
+                    $val
+                ));
+                rom.$field = $val;
+            }
+        };
+    }
+
+    apply!(mapper, entry.mapper);
+    apply!(submapper, entry.submapper);
+    apply!(prg_ram_size, entry.prg_ram_size);
+    apply!(prg_nvram_size, entry.prg_nvram_size);
+    apply!(chr_ram_size, entry.chr_ram_size);
+    apply!(chr_nvram_size, entry.chr_nvram_size);
+    apply!(
+        battery,
+        entry.prg_nvram_size > 0 || entry.chr_nvram_size > 0
+    );
+    apply!(timing, entry.timing);
+
+    match entry.mirroring {
+        Some(DbMirroring::Horizontal) => {
+            apply!(vert_mirrored, false);
+            apply!(four_screen, false);
+        }
+        Some(DbMirroring::Vertical) => {
+            apply!(vert_mirrored, true);
+            apply!(four_screen, false);
+        }
+        Some(DbMirroring::FourScreen) => apply!(four_screen, true),
+        None => {}
+    }
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "Header corrected from database ({}):\n  {}",
+            entry.title,
+            changes.join("\n  ")
+        ))
+    }
+}
+
+fn db_parse(text: &str) -> Result<Vec<DbEntry>, String> {
+    let mut entries = vec![];
+
+    for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match db_parse_line(line) {
+            Ok(entry) => entries.push(entry),
+            Err(err_str) => return Err(format!("Error at line {}: {}", line_num + 1, err_str)),
+        }
+    }
+
+    Ok(entries)
+}
+
+fn db_parse_line(line: &str) -> Result<DbEntry, String> {
+    let mut fields: Vec<&str> = vec![];
+    let mut rest = line;
+
+    for _ in 0..NUM_FIELDS {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return Err(String::from("Too few fields."));
+        }
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    let crc32 = u32::from_str_radix(fields[0], 16).map_err(|_| "Invalid CRC32.")?;
+
+    let sha1 = match fields[1] {
+        "-" => None,
+        sha1_str => {
+            let mut digest = [0; 20];
+            hex::decode_to_slice(sha1_str, &mut digest).map_err(|_| "Invalid SHA-1.")?;
+            Some(digest)
+        }
+    };
+
+    let (mapper, submapper) = fields[2].split_once('.').unwrap_or((fields[2], "0"));
+
+    let mirroring = match fields[3] {
+        "H" => Some(DbMirroring::Horizontal),
+        "V" => Some(DbMirroring::Vertical),
+        "4" => Some(DbMirroring::FourScreen),
+        "-" => None,
+        _ => return Err(String::from("Invalid mirroring.")),
+    };
+
+    let size = |field: &str| field.parse::<usize>().map_err(|_| "Invalid size.");
+
+    let timing = match fields[8] {
+        "N" => Timing::Ntsc,
+        "P" => Timing::Pal,
+        "M" => Timing::Multi,
+        "D" => Timing::Dendy,
+        _ => return Err(String::from("Invalid timing.")),
+    };
+
+    Ok(DbEntry {
+        crc32,
+        sha1,
+        title: rest.trim().to_string(),
+        mapper: mapper.parse().map_err(|_| "Invalid mapper.")?,
+        submapper: submapper.parse().map_err(|_| "Invalid submapper.")?,
+        mirroring,
+        prg_ram_size: size(fields[4])?,
+        prg_nvram_size: size(fields[5])?,
+        chr_ram_size: size(fields[6])?,
+        chr_nvram_size: size(fields[7])?,
+        timing,
+    })
+}
@@ -1,60 +1,166 @@
+#[path = "game_db.rs"]
+pub mod game_db;
+
+#[path = "unif.rs"]
+pub mod unif;
+
+#[cfg(test)]
+mod test;
+
 const HDR_SIZE: usize = 16;
-const TRAINER_SIZE: usize = 512;
+pub const TRAINER_SIZE: usize = 512;
+const INES_MAGIC: &[u8] = b"NES\x1A";
+/// fwNES headered and raw Famicom Disk System images.
+const FDS_MAGIC: &[u8] = b"FDS\x1A";
+const FDS_DISK_MAGIC: &[u8] = b"\x01*NINTENDO-HVC*";
+/// The mapper number NES 2.0 uses for the Famicom Disk System.
+pub const MAPPER_FDS: u16 = 20;
+const ROM_SIZE_EXPONENT: usize = 0xF;
+use crate::error::NesError;
 use crate::{KB_16, KB_8};
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Timing {
+    Ntsc,
+    Pal,
+    Multi,
+    Dendy,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ConsoleType {
+    Nes,
+    VsSystem {
+        ppu_type: u8,
+        hw_type: u8,
+    },
+    Playchoice,
+    /// One of the NES 2.0 extended console types (byte 13, low nibble).
+    Extended(u8),
+}
+
 pub struct Rom {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
-    pub mapper: u8,
-    pub prg_ram_size: u16,
-    pub chr_ram_size: u16,
+    /// The 512 byte trainer, loaded at $7000 on power-on.
+    pub trainer: Option<Vec<u8>>,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
     pub vert_mirrored: bool,
+    pub four_screen: bool,
+    pub battery: bool,
+    pub nes_2: bool,
+    pub timing: Timing,
+    pub console_type: ConsoleType,
+    pub misc_roms: u8,
+    pub default_expansion: u8,
+    /// The board name, for ROMs loaded from UNIF files.
+    pub board: Option<String>,
 }
 
-pub fn rom_parse(data: &[u8]) -> Result<Rom, String> {
-    let ines_2 = (data[7] & 0x0C) == 0x08;
-    let prg_rom_size = data[4] as usize * KB_16;
-    let chr_rom_size = data[5] as usize * KB_8;
+pub fn rom_parse(data: &[u8]) -> Result<Rom, NesError> {
+    // Disk images need the FDS BIOS, which can't be loaded yet.
+    if data.starts_with(FDS_MAGIC) || data.starts_with(FDS_DISK_MAGIC) {
+        return Err(NesError::MissingBios);
+    }
+
+    if data.len() < HDR_SIZE {
+        return Err(NesError::TruncatedFile {
+            expected: HDR_SIZE,
+            actual: data.len(),
+        });
+    }
+
+    if !data.starts_with(INES_MAGIC) {
+        return Err(NesError::BadMagic);
+    }
+
+    let nes_2 = (data[7] & 0x0C) == 0x08;
+    let (prg_rom_size, chr_rom_size) = if nes_2 {
+        (
+            nes_2_rom_size(data[4], data[9] & 0x0F, KB_16),
+            nes_2_rom_size(data[5], data[9] >> 4, KB_8),
+        )
+    } else {
+        (data[4] as usize * KB_16, data[5] as usize * KB_8)
+    };
     let trainer_present = (data[6] & 0x04) == 0x04;
     let prg_rom_offset = HDR_SIZE + if trainer_present { TRAINER_SIZE } else { 0 };
-    let chr_rom_offset = prg_rom_offset + prg_rom_size;
-    let mapper: u8 = (data[6] >> 4) | (data[7] & 0xF0);
+    let chr_rom_offset = prg_rom_offset.saturating_add(prg_rom_size);
+    let mut mapper = ((data[6] >> 4) | (data[7] & 0xF0)) as u16;
     let vert_mirrored = (data[6] & 0x1) == 0x1;
+    let battery = (data[6] & 0x2) == 0x2;
+    let four_screen = (data[6] & 0x8) == 0x8;
 
-    let prg_ram_size: u16 = if ines_2 {
-        let shift_count = if (data[10] & 0xF0) != 0 {
-            (data[10] & 0xF0) >> 4
-        } else {
-            data[10] & 0x0F
+    let console_type = match data[7] & 0x3 {
+        0 => ConsoleType::Nes,
+        1 => ConsoleType::VsSystem {
+            ppu_type: if nes_2 { data[13] & 0x0F } else { 0 },
+            hw_type: if nes_2 { data[13] >> 4 } else { 0 },
+        },
+        2 => ConsoleType::Playchoice,
+        _ => ConsoleType::Extended(if nes_2 { data[13] & 0x0F } else { 0 }),
+    };
+
+    let (submapper, prg_ram_size, prg_nvram_size, chr_ram_size, chr_nvram_size, timing) = if nes_2 {
+        mapper |= ((data[8] & 0x0F) as u16) << 8;
+
+        let timing = match data[12] & 0x3 {
+            0 => Timing::Ntsc,
+            1 => Timing::Pal,
+            2 => Timing::Multi,
+            _ => Timing::Dendy,
         };
 
-        if shift_count == 0 {
-            0
-        } else {
-            0x40 << shift_count
-        }
+        (
+            data[8] >> 4,
+            nes_2_ram_size(data[10] & 0x0F),
+            nes_2_ram_size(data[10] >> 4),
+            nes_2_ram_size(data[11] & 0x0F),
+            nes_2_ram_size(data[11] >> 4),
+            timing,
+        )
     } else {
-        KB_8 as u16
+        (
+            0,
+            KB_8,
+            0,
+            if chr_rom_size == 0 { KB_8 } else { 0 },
+            0,
+            Timing::Ntsc,
+        )
     };
 
-    let chr_ram_size: u16 = if ines_2 {
-        let shift_count = if (data[11] & 0xF0) != 0 {
-            (data[11] & 0xF0) >> 4
-        } else {
-            data[11] & 0x0F
-        };
-
-        if shift_count == 0 {
-            0
-        } else {
-            0x40 << shift_count
-        }
-    } else if chr_rom_size == 0 {
-        KB_8 as u16
+    let (misc_roms, default_expansion) = if nes_2 {
+        (data[14] & 0x3, data[15] & 0x3F)
     } else {
-        0
+        (0, 0)
     };
 
+    if prg_rom_size == 0 {
+        return Err(NesError::BadSize {
+            region: "PRG ROM",
+            size: prg_rom_size,
+        });
+    }
+
+    let rom_end = chr_rom_offset.saturating_add(chr_rom_size);
+    if data.len() < rom_end {
+        return Err(NesError::TruncatedFile {
+            expected: rom_end,
+            actual: data.len(),
+        });
+    }
+
+    let trainer = if trainer_present {
+        Some(data[HDR_SIZE..HDR_SIZE + TRAINER_SIZE].to_vec())
+    } else {
+        None
+    };
     let mut prg_rom: Vec<u8> = vec![0; prg_rom_size];
     let mut chr_rom: Vec<u8> = vec![0; chr_rom_size];
     prg_rom.copy_from_slice(&data[prg_rom_offset..prg_rom_offset + prg_rom_size]);
@@ -63,24 +169,88 @@ pub fn rom_parse(data: &[u8]) -> Result<Rom, String> {
     Ok(Rom {
         prg_rom,
         chr_rom,
+        trainer,
         mapper,
+        submapper,
         prg_ram_size,
+        prg_nvram_size,
         chr_ram_size,
+        chr_nvram_size,
         vert_mirrored,
+        four_screen,
+        battery,
+        nes_2,
+        timing,
+        console_type,
+        misc_roms,
+        default_expansion,
+        board: None,
     })
 }
 
 pub fn rom_get_info(rom: &Rom) -> String {
     format!(
-        "PRG ROM Size: {}\
+        "Format:       {}\
+        \nPRG ROM Size: {}\
         \nCHR ROM Size: {}\
         \nPRG RAM Size: {}\
+        \nPRG NVRAM:    {}\
         \nCHR RAM Size: {}\
-        \nMapper:       {}",
+        \nCHR NVRAM:    {}\
+        \nMapper:       {}\
+        \nSubmapper:    {}\
+        \nMirroring:    {}\
+        \nBattery:      {}\
+        \nTrainer:      {}\
+        \nTiming:       {:?}\
+        \nConsole:      {:?}\
+        \nMisc ROMs:    {}\
+        \nExpansion:    {:02X}",
+        match &rom.board {
+            Some(board) => format!("UNIF ({})", board),
+            None if rom.nes_2 => String::from("NES 2.0"),
+            None => String::from("iNES"),
+        },
         rom.prg_rom.len(),
         rom.chr_rom.len(),
         rom.prg_ram_size,
+        rom.prg_nvram_size,
         rom.chr_ram_size,
-        rom.mapper
+        rom.chr_nvram_size,
+        rom.mapper,
+        rom.submapper,
+        if rom.four_screen {
+            "Four-screen"
+        } else if rom.vert_mirrored {
+            "Vertical"
+        } else {
+            "Horizontal"
+        },
+        rom.battery,
+        rom.trainer.is_some(),
+        rom.timing,
+        rom.console_type,
+        rom.misc_roms,
+        rom.default_expansion
     )
 }
+
+/// Decodes an NES 2.0 ROM size from its LSB byte and MSB nibble. An MSB
+/// nibble of $F selects the exponent-multiplier form, 2^E * (MM * 2 + 1).
+fn nes_2_rom_size(lsb: u8, msb: u8, unit: usize) -> usize {
+    if msb as usize == ROM_SIZE_EXPONENT {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = ((lsb & 0x3) as usize * 2) + 1;
+        2usize.saturating_pow(exponent).saturating_mul(multiplier)
+    } else {
+        (((msb as usize) << 8) | lsb as usize) * unit
+    }
+}
+
+fn nes_2_ram_size(shift_count: u8) -> usize {
+    if shift_count == 0 {
+        0
+    } else {
+        0x40 << shift_count
+    }
+}
@@ -0,0 +1,199 @@
+use super::*;
+
+fn ines_file(hdr: [u8; HDR_SIZE], prg_size: usize, chr_size: usize) -> Vec<u8> {
+    let mut data = hdr.to_vec();
+    data.resize(HDR_SIZE + prg_size + chr_size, 0);
+    data
+}
+
+#[test]
+fn ines_1() {
+    let hdr = [
+        0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01, 0x43, 0x10, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let rom = rom_parse(&ines_file(hdr, 2 * KB_16, KB_8)).unwrap();
+
+    assert!(!rom.nes_2);
+    assert_eq!(rom.mapper, 0x14);
+    assert_eq!(rom.prg_rom.len(), 2 * KB_16);
+    assert_eq!(rom.chr_rom.len(), KB_8);
+    assert_eq!(rom.prg_ram_size, KB_8);
+    assert_eq!(rom.chr_ram_size, 0);
+    assert!(rom.vert_mirrored);
+    assert!(rom.battery);
+    assert_eq!(rom.timing, Timing::Ntsc);
+}
+
+#[test]
+fn nes_2() {
+    let hdr = [
+        0x4E, 0x45, 0x53, 0x1A, 0x01, 0x00, 0x4A, 0x59, 0x31, 0x00, 0x70, 0x07, 0x03, 0x21, 0x01,
+        0x05,
+    ];
+    let rom = rom_parse(&ines_file(hdr, KB_16, 0)).unwrap();
+
+    assert!(rom.nes_2);
+    assert_eq!(rom.mapper, 0x154);
+    assert_eq!(rom.submapper, 3);
+    assert_eq!(rom.prg_ram_size, 0);
+    assert_eq!(rom.prg_nvram_size, KB_8);
+    assert_eq!(rom.chr_ram_size, KB_8);
+    assert_eq!(rom.chr_nvram_size, 0);
+    assert!(rom.four_screen);
+    assert!(rom.battery);
+    assert_eq!(rom.timing, Timing::Dendy);
+    assert_eq!(
+        rom.console_type,
+        ConsoleType::VsSystem {
+            ppu_type: 1,
+            hw_type: 2
+        }
+    );
+    assert_eq!(rom.misc_roms, 1);
+    assert_eq!(rom.default_expansion, 5);
+}
+
+#[test]
+fn nes_2_exponent_size() {
+    let mut hdr = [
+        0x4E, 0x45, 0x53, 0x1A, 0x00, 0x00, 0x00, 0x08, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    hdr[4] = (14 << 2) | 0x1;
+    hdr[9] = 0x0F;
+    let rom = rom_parse(&ines_file(hdr, 3 * KB_16, 0)).unwrap();
+
+    assert_eq!(rom.prg_rom.len(), 3 * KB_16);
+}
+
+#[test]
+fn trainer() {
+    let hdr = [
+        0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let mut data = ines_file(hdr, TRAINER_SIZE, 0);
+    data[HDR_SIZE..HDR_SIZE + TRAINER_SIZE].fill(0xAA);
+    data.resize(HDR_SIZE + TRAINER_SIZE + KB_16, 0xBB);
+    data.resize(HDR_SIZE + TRAINER_SIZE + KB_16 + KB_8, 0xCC);
+
+    let rom = rom_parse(&data).unwrap();
+
+    assert_eq!(rom.trainer, Some(vec![0xAA; TRAINER_SIZE]));
+    assert_eq!(rom.prg_rom, vec![0xBB; KB_16]);
+    assert_eq!(rom.chr_rom, vec![0xCC; KB_8]);
+}
+
+#[test]
+fn truncated() {
+    let hdr = [
+        0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let data = ines_file(hdr, KB_16, 0);
+
+    assert_eq!(
+        rom_parse(&data).err(),
+        Some(NesError::TruncatedFile {
+            expected: HDR_SIZE + (2 * KB_16) + KB_8,
+            actual: data.len()
+        })
+    );
+    assert!(rom_parse(&data[..8]).is_err());
+    assert_eq!(rom_parse(&[0; HDR_SIZE]).err(), Some(NesError::BadMagic));
+
+    let mut fds = b"FDS\x1A\x01".to_vec();
+    fds.resize(HDR_SIZE, 0);
+    assert_eq!(rom_parse(&fds).err(), Some(NesError::MissingBios));
+    let mut disk = b"\x01*NINTENDO-HVC*".to_vec();
+    disk.resize(HDR_SIZE * 2, 0);
+    assert_eq!(rom_parse(&disk).err(), Some(NesError::MissingBios));
+}
+
+#[test]
+fn game_db() {
+    use game_db::*;
+
+    let mut rom = rom_parse(&ines_file(
+        [
+            0x4E, 0x45, 0x53, 0x1A, 0x01, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        KB_16,
+        0,
+    ))
+    .unwrap();
+    rom.prg_rom = b"12345".to_vec();
+    rom.chr_rom = b"6789".to_vec();
+
+    assert_eq!(db_lookup(&rom.prg_rom, &rom.chr_rom), None);
+    assert_eq!(db_apply(&mut rom), None);
+
+    let num_entries = db_load(
+        "# comment
+        CBF43926 F7C3BC1D808E04732ADF679965CCC34CA7AE3441 4.1 H 0 8192 0 0 P Test Game #1
+        CBF43926 0000000000000000000000000000000000000000 5.0 - 0 0 0 0 N Wrong SHA-1",
+    )
+    .unwrap();
+    assert_eq!(num_entries, 2);
+
+    let entry = db_lookup(&rom.prg_rom, &rom.chr_rom).unwrap();
+    assert_eq!(entry.title, "Test Game #1");
+
+    assert!(db_apply(&mut rom).is_some());
+    assert_eq!(rom.mapper, 4);
+    assert_eq!(rom.submapper, 1);
+    assert!(!rom.vert_mirrored);
+    assert_eq!(rom.prg_ram_size, 0);
+    assert_eq!(rom.prg_nvram_size, KB_8);
+    assert!(rom.battery);
+    assert_eq!(rom.timing, Timing::Pal);
+    assert_eq!(db_apply(&mut rom), None);
+
+    assert_eq!(
+        db_load("# comment\nCBF43926 - 4.1 X 0 0 0 0 N Bad mirroring"),
+        Err(String::from("Error at line 2: Invalid mirroring."))
+    );
+}
+
+fn unif_chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend((data.len() as u32).to_le_bytes());
+    chunk.extend(data);
+    chunk
+}
+
+#[test]
+fn unif() {
+    let mut data = b"UNIF".to_vec();
+    data.extend(7u32.to_le_bytes());
+    data.resize(32, 0);
+    data.extend(unif_chunk(b"MAPR", b"NES-SNROM\0"));
+    data.extend(unif_chunk(b"PRG1", &[0x22; KB_16]));
+    data.extend(unif_chunk(b"PRG0", &[0x11; KB_16]));
+    data.extend(unif_chunk(b"MIRR", &[1]));
+    data.extend(unif_chunk(b"BATR", &[1]));
+    data.extend(unif_chunk(b"TVCI", &[1]));
+    data.extend(unif_chunk(b"CTRL", &[0x03]));
+
+    let rom = unif::unif_parse(&data).unwrap();
+
+    assert_eq!(rom.board.as_deref(), Some("NES-SNROM"));
+    assert_eq!(rom.mapper, 1);
+    assert_eq!(rom.prg_rom.len(), 2 * KB_16);
+    assert_eq!(rom.prg_rom[0], 0x11);
+    assert_eq!(rom.prg_rom[KB_16], 0x22);
+    assert!(rom.chr_rom.is_empty());
+    assert_eq!(rom.chr_ram_size, KB_8);
+    assert!(rom.vert_mirrored);
+    assert!(rom.battery);
+    assert_eq!(rom.timing, Timing::Pal);
+    assert_eq!(rom.default_expansion, 0x08);
+
+    assert_eq!(unif::board_mapper("hvc-tlrom"), Some((4, 0)));
+    assert_eq!(unif::board_mapper("CNROM"), Some((3, 0)));
+
+    let mut data = data[..32].to_vec();
+    data.extend(unif_chunk(b"MAPR", b"UNL-FOO\0"));
+    data.extend(unif_chunk(b"PRG0", &[0; KB_16]));
+    assert_eq!(
+        unif::unif_parse(&data).err(),
+        Some(NesError::UnknownBoard(String::from("UNL-FOO")))
+    );
+}
@@ -0,0 +1,222 @@
+use super::{ConsoleType, Rom, Timing};
+use crate::error::NesError;
+use crate::KB_8;
+
+const UNIF_MAGIC: &[u8] = b"UNIF";
+const UNIF_HDR_SIZE: usize = 32;
+const CHUNK_HDR_SIZE: usize = 8;
+const NUM_ROM_CHUNKS: usize = 16;
+const BOARD_PREFIXES: [&str; 8] = [
+    "NES-", "HVC-", "UNL-", "BTL-", "BMC-", "IREM-", "KONAMI-", "TAITO-",
+];
+
+const MIRR_VERTICAL: u8 = 1;
+const MIRR_FOUR_SCREEN: u8 = 4;
+const TVCI_PAL: u8 = 1;
+const TVCI_MULTI: u8 = 2;
+
+/// UNIF board names (without their vendor prefix) and the iNES mapper and
+/// submapper implementing them.
+const BOARDS: &[(&str, u16, u8)] = &[
+    ("NROM", 0, 0),
+    ("NROM-128", 0, 0),
+    ("NROM-256", 0, 0),
+    ("RROM", 0, 0),
+    ("RROM-128", 0, 0),
+    ("SAROM", 1, 0),
+    ("SBROM", 1, 0),
+    ("SCROM", 1, 0),
+    ("SEROM", 1, 0),
+    ("SFROM", 1, 0),
+    ("SGROM", 1, 0),
+    ("SHROM", 1, 0),
+    ("SJROM", 1, 0),
+    ("SKROM", 1, 0),
+    ("SLROM", 1, 0),
+    ("SL1ROM", 1, 0),
+    ("SNROM", 1, 0),
+    ("SOROM", 1, 0),
+    ("SUROM", 1, 0),
+    ("SXROM", 1, 0),
+    ("UNROM", 2, 0),
+    ("UOROM", 2, 0),
+    ("CNROM", 3, 0),
+    ("TBROM", 4, 0),
+    ("TEROM", 4, 0),
+    ("TFROM", 4, 0),
+    ("TGROM", 4, 0),
+    ("TKROM", 4, 0),
+    ("TLROM", 4, 0),
+    ("TL1ROM", 4, 0),
+    ("TNROM", 4, 0),
+    ("TR1ROM", 4, 0),
+    ("TSROM", 4, 0),
+    ("TVROM", 4, 0),
+    ("HKROM", 4, 1),
+    ("ELROM", 5, 0),
+    ("EKROM", 5, 0),
+    ("ETROM", 5, 0),
+    ("EWROM", 5, 0),
+    ("AMROM", 7, 0),
+    ("ANROM", 7, 0),
+    ("AOROM", 7, 0),
+    ("PNROM", 9, 0),
+    ("PEEOROM", 9, 0),
+    ("FJROM", 10, 0),
+    ("FKROM", 10, 0),
+    ("CPROM", 13, 0),
+    ("BNROM", 34, 0),
+    ("GNROM", 66, 0),
+    ("MHROM", 66, 0),
+    ("TLSROM", 118, 0),
+    ("TKSROM", 118, 0),
+    ("TQROM", 119, 0),
+];
+
+/// NES 2.0 default expansion devices for the CTRL chunk's bits, in the
+/// order they are preferred when several are set.
+const CTRL_DEVICES: [(u8, u8); 6] = [
+    (0x02, 0x08), // Zapper
+    (0x04, 0x1F), // R.O.B.
+    (0x08, 0x0F), // Arkanoid controller
+    (0x10, 0x0B), // Power Pad
+    (0x20, 0x02), // Four Score
+    (0x01, 0x01), // Standard controllers
+];
+
+pub fn is_unif(data: &[u8]) -> bool {
+    data.starts_with(UNIF_MAGIC)
+}
+
+pub fn unif_parse(data: &[u8]) -> Result<Rom, NesError> {
+    if data.len() < UNIF_HDR_SIZE {
+        return Err(NesError::TruncatedFile {
+            expected: UNIF_HDR_SIZE,
+            actual: data.len(),
+        });
+    }
+
+    if !is_unif(data) {
+        return Err(NesError::BadMagic);
+    }
+
+    let mut board = None;
+    let mut prg_chunks: [&[u8]; NUM_ROM_CHUNKS] = [&[]; NUM_ROM_CHUNKS];
+    let mut chr_chunks: [&[u8]; NUM_ROM_CHUNKS] = [&[]; NUM_ROM_CHUNKS];
+    let mut mirroring = 0;
+    let mut battery = false;
+    let mut timing = Timing::Ntsc;
+    let mut default_expansion = 0;
+    let mut offset = UNIF_HDR_SIZE;
+
+    while offset + CHUNK_HDR_SIZE <= data.len() {
+        let chunk_id = &data[offset..offset + 4];
+        let chunk_len = u32::from_le_bytes([
+            data[offset + 4],
+            data[offset + 5],
+            data[offset + 6],
+            data[offset + 7],
+        ]) as usize;
+        let chunk_start = offset + CHUNK_HDR_SIZE;
+        let chunk_end = chunk_start.saturating_add(chunk_len);
+
+        if chunk_end > data.len() {
+            return Err(NesError::TruncatedFile {
+                expected: chunk_end,
+                actual: data.len(),
+            });
+        }
+
+        let chunk = &data[chunk_start..chunk_end];
+
+        match chunk_id {
+            b"MAPR" => {
+                let end = chunk.iter().position(|&b| b == 0).unwrap_or(chunk.len());
+                board = Some(String::from_utf8_lossy(&chunk[..end]).into_owned());
+            }
+            b"MIRR" => mirroring = chunk.first().copied().unwrap_or(0),
+            b"BATR" => battery = chunk.first().is_some_and(|&b| b != 0),
+            b"TVCI" => {
+                timing = match chunk.first() {
+                    Some(&TVCI_PAL) => Timing::Pal,
+                    Some(&TVCI_MULTI) => Timing::Multi,
+                    _ => Timing::Ntsc,
+                }
+            }
+            b"CTRL" => {
+                let ctrl = chunk.first().copied().unwrap_or(0);
+                default_expansion = CTRL_DEVICES
+                    .iter()
+                    .find(|(mask, _)| ctrl & mask != 0)
+                    .map_or(0, |&(_, device)| device);
+            }
+            [b'P', b'R', b'G', n] => {
+                if let Some(idx) = rom_chunk_idx(*n) {
+                    prg_chunks[idx] = chunk;
+                }
+            }
+            [b'C', b'H', b'R', n] => {
+                if let Some(idx) = rom_chunk_idx(*n) {
+                    chr_chunks[idx] = chunk;
+                }
+            }
+            _ => {}
+        }
+
+        offset = chunk_end;
+    }
+
+    let board = board.ok_or(NesError::UnknownBoard(String::new()))?;
+    let (mapper, submapper) =
+        board_mapper(&board).ok_or_else(|| NesError::UnknownBoard(board.clone()))?;
+
+    let prg_rom = prg_chunks.concat();
+    let chr_rom = chr_chunks.concat();
+
+    if prg_rom.is_empty() {
+        return Err(NesError::BadSize {
+            region: "PRG ROM",
+            size: 0,
+        });
+    }
+
+    Ok(Rom {
+        mapper,
+        submapper,
+        prg_ram_size: KB_8,
+        prg_nvram_size: 0,
+        chr_ram_size: if chr_rom.is_empty() { KB_8 } else { 0 },
+        chr_nvram_size: 0,
+        prg_rom,
+        chr_rom,
+        trainer: None,
+        vert_mirrored: mirroring == MIRR_VERTICAL,
+        four_screen: mirroring == MIRR_FOUR_SCREEN,
+        battery,
+        nes_2: false,
+        timing,
+        console_type: ConsoleType::Nes,
+        misc_roms: 0,
+        default_expansion,
+        board: Some(board),
+    })
+}
+
+/// Looks up a board by name, ignoring case and any vendor prefix.
+pub fn board_mapper(board: &str) -> Option<(u16, u8)> {
+    let board = board.to_ascii_uppercase();
+    let name = BOARD_PREFIXES
+        .iter()
+        .find_map(|prefix| board.strip_prefix(prefix))
+        .unwrap_or(&board);
+
+    BOARDS
+        .iter()
+        .find(|(board_name, _, _)| *board_name == name)
+        .map(|&(_, mapper, submapper)| (mapper, submapper))
+}
+
+/// PRG and CHR chunks are numbered with a single hex digit, 0-F.
+fn rom_chunk_idx(n: u8) -> Option<usize> {
+    (n as char).to_digit(16).map(|idx| idx as usize)
+}
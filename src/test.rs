@@ -0,0 +1,48 @@
+use super::*;
+use assemble::assemble;
+use rom::rom_parse;
+
+const PRG_START: usize = 0x10;
+
+/// An NROM-128 image running `src` from $C000.
+pub(crate) fn test_nes(src: &str) -> Nes {
+    let mut data = vec![0; PRG_START + KB_16 + KB_8];
+    data[..8].copy_from_slice(&[0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01, 0x00, 0x00]);
+
+    let prg = assemble(src).unwrap();
+    data[PRG_START..PRG_START + prg.len()].copy_from_slice(&prg);
+    data[PRG_START + 0x3FFC..PRG_START + 0x3FFE].copy_from_slice(&[0x00, 0xC0]);
+
+    Nes::init(&rom_parse(&data).unwrap()).unwrap()
+}
+
+fn dma_cycles(nes: &mut Nes) -> u32 {
+    let mut frame = [0; FRAME_SIZE_PIXELS];
+    let mut cycles = 0;
+
+    while !nes.dma_flag {
+        nes.tick(&mut frame);
+    }
+
+    while nes.dma_flag {
+        let dma_active = nes.cpu.ins_complete();
+        let cpu_clock = nes.cpu_clock;
+        nes.tick(&mut frame);
+
+        if nes.cpu_clock != cpu_clock && dma_active {
+            cycles += 1;
+        }
+    }
+
+    cycles
+}
+
+#[test]
+fn dma_alignment() {
+    let mut even = test_nes("LDA #$02\nSTA $4014\nJMP $C005");
+    let mut odd = test_nes("LDA $00\nLDA #$02\nSTA $4014\nJMP $C007");
+    let (even, odd) = (dma_cycles(&mut even), dma_cycles(&mut odd));
+
+    assert!([513, 514].contains(&even));
+    assert_eq!(even.abs_diff(odd), 1);
+}
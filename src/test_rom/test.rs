@@ -0,0 +1,63 @@
+use super::*;
+use crate::test::test_nes;
+
+const SIGNATURE_SRC: &str = "LDA #$DE
+STA $6001
+LDA #$B0
+STA $6002
+LDA #$61
+STA $6003";
+
+#[test]
+fn reset_then_pass() {
+    let nes = &mut test_nes(&format!(
+        "{}
+LDA $10
+CMP #$5A
+BEQ $0C
+LDA #$5A
+STA $10
+LDA #$81
+STA $6000
+JMP $C01E
+LDA #$4F
+STA $6004
+LDA #$4B
+STA $6005
+LDA #$00
+STA $6006
+STA $6000
+JMP $C033",
+        SIGNATURE_SRC
+    ));
+
+    assert_eq!(
+        test_rom_run(nes, 60),
+        TestRomResult::Passed(String::from("OK"))
+    );
+}
+
+#[test]
+fn fail() {
+    let nes = &mut test_nes(&format!(
+        "{}
+LDA #$03
+STA $6000
+JMP $C014",
+        SIGNATURE_SRC
+    ));
+
+    assert_eq!(
+        test_rom_run(nes, 60),
+        TestRomResult::Failed(3, String::new())
+    );
+}
+
+#[test]
+fn timeout_and_screen_hash() {
+    let nes = &mut test_nes("JMP $C000");
+    assert_eq!(test_rom_run(nes, 10), TestRomResult::TimedOut);
+
+    let hash = test_rom_screen_hash(&mut test_nes("JMP $C000"), 5);
+    assert_eq!(test_rom_screen_hash(&mut test_nes("JMP $C000"), 5), hash);
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod test;
+
+use crate::hash::crc32;
+use crate::{Nes, FRAME_SIZE_BYTES};
+
+const STATUS_ADDR: u16 = 0x6000;
+const SIGNATURE_ADDR: u16 = 0x6001;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const TEXT_ADDR: u16 = 0x6004;
+const TEXT_END: u16 = 0x7FFF;
+const STATUS_RUNNING: u8 = 0x80;
+const STATUS_RESET: u8 = 0x81;
+/// Test ROMs asking for a reset need it to be held off for at least 100 ms.
+const RESET_DELAY_FRAMES: u32 = 7;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TestRomResult {
+    Passed(String),
+    /// The result code and message.
+    Failed(u8, String),
+    TimedOut,
+}
+
+/// Runs a test ROM that reports through the $6000 protocol used by blargg's
+/// tests until it finishes or `max_frames` have passed. Once the signature
+/// is at $6001, $6000 is $80 while running, $81 when it wants a reset, and
+/// otherwise the result code, 0 meaning passed. The message is at $6004.
+pub fn test_rom_run(nes: &mut Nes, max_frames: u32) -> TestRomResult {
+    let mut frame = [0; FRAME_SIZE_BYTES];
+    let mut reset_frame = None;
+
+    for frame_num in 0..max_frames {
+        nes.frame(&mut frame);
+
+        if (0..SIGNATURE.len())
+            .any(|idx| nes.cart_read(SIGNATURE_ADDR + idx as u16) != SIGNATURE[idx])
+        {
+            continue;
+        }
+
+        match nes.cart_read(STATUS_ADDR) {
+            STATUS_RUNNING => (),
+            STATUS_RESET => match reset_frame {
+                None => reset_frame = Some(frame_num + RESET_DELAY_FRAMES),
+                Some(reset_at) if frame_num >= reset_at => {
+                    nes.reset();
+                    reset_frame = None;
+                }
+                _ => (),
+            },
+            0 => return TestRomResult::Passed(test_rom_text(nes)),
+            code => return TestRomResult::Failed(code, test_rom_text(nes)),
+        }
+    }
+
+    TestRomResult::TimedOut
+}
+
+/// For test ROMs that only show their results on screen, the CRC32 of the
+/// RGB frame after running for `frames` frames.
+pub fn test_rom_screen_hash(nes: &mut Nes, frames: u32) -> u32 {
+    let mut frame = [0; FRAME_SIZE_BYTES];
+
+    for _ in 0..frames {
+        nes.frame(&mut frame);
+    }
+
+    crc32(&frame)
+}
+
+fn test_rom_text(nes: &mut Nes) -> String {
+    let text: Vec<u8> = (TEXT_ADDR..=TEXT_END)
+        .map(|addr| nes.cart_read(addr))
+        .take_while(|&byte| byte != 0)
+        .collect();
+
+    String::from_utf8_lossy(&text).trim_end().to_string()
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod test;
+
+use lazy_static::lazy_static;
+
+const CRC32_POLY: u32 = 0xEDB88320;
+const ADLER32_MOD: u32 = 65521;
+const SHA1_BLOCK_SIZE: usize = 64;
+
+lazy_static! {
+    static ref CRC32_TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut crc = n as u32;
+            for _ in 0..8 {
+                crc = if crc & 0x1 == 0x1 {
+                    CRC32_POLY ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+            }
+            *entry = crc;
+        }
+
+        table
+    };
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
+/// Continues a CRC32 over more data, e.g. `crc32_update(crc32(a), b)` is the
+/// CRC32 of `a` followed by `b`.
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+
+    for &byte in data {
+        crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+
+    !crc
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1, 0);
+
+    for &byte in data {
+        a = (a + byte as u32) % ADLER32_MOD;
+        b = (b + a) % ADLER32_MOD;
+    }
+
+    (b << 16) | a
+}
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % SHA1_BLOCK_SIZE != 56 {
+        msg.push(0);
+    }
+    msg.extend(((data.len() as u64) * 8).to_be_bytes());
+
+    for block in msg.chunks(SHA1_BLOCK_SIZE) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([
+                block[i * 4],
+                block[i * 4 + 1],
+                block[i * 4 + 2],
+                block[i * 4 + 3],
+            ]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, val) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(val);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (chunk, val) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&val.to_be_bytes());
+    }
+    digest
+}
@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn crc() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xCBF43926);
+}
+
+#[test]
+fn adler() {
+    assert_eq!(adler32(b""), 1);
+    assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+}
+
+#[test]
+fn sha() {
+    assert_eq!(
+        hex::encode(sha1(b"abc")),
+        "a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+    assert_eq!(
+        hex::encode(sha1(&[b'a'; 64])),
+        "0098ba824b5c16427bd7a1122a5a442a25ec644d"
+    );
+}
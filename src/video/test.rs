@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn encode_formats() {
+    let rgb = Rgb(0x12, 0x34, 0x56);
+    let encode = |format: PixelFormat| {
+        let mut out = vec![0; format.bytes_per_pixel()];
+        format.encode(rgb, &mut out);
+        out
+    };
+
+    assert_eq!(encode(PixelFormat::Rgb24), [0x12, 0x34, 0x56]);
+    assert_eq!(encode(PixelFormat::Rgba8888), [0x12, 0x34, 0x56, 0xFF]);
+    assert_eq!(encode(PixelFormat::Bgra8888), [0x56, 0x34, 0x12, 0xFF]);
+    assert_eq!(
+        encode(PixelFormat::Rgb565),
+        ((0x02u16 << 11) | (0x0D << 5) | 0x0A).to_ne_bytes()
+    );
+    assert_eq!(encode(PixelFormat::Xrgb8888), 0x123456u32.to_ne_bytes());
+}
+
+#[test]
+fn pixel_buffer() {
+    let mut buffer = PixelBuffer::init(PixelFormat::Bgra8888);
+    assert_eq!(buffer.data().len(), FRAME_SIZE_PIXELS * 4);
+
+    buffer.put_pixel(2, 1, 0x30, Rgb(1, 2, 3));
+    let idx = buffer.pitch() + 8;
+    assert_eq!(buffer.data()[idx..idx + 4], [3, 2, 1, 0xFF]);
+
+    let mut rgb = [0; FRAME_SIZE_BYTES];
+    rgb.put_pixel(DISPLAY_WIDTH - 1, DISPLAY_HEIGHT - 1, 0x30, Rgb(1, 2, 3));
+    assert_eq!(rgb[FRAME_SIZE_BYTES - 3..], [1, 2, 3]);
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod test;
+
+use crate::palette::Rgb;
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FRAME_SIZE_BYTES, FRAME_SIZE_PIXELS};
+
+/// Receives each pixel as the PPU draws it.
+pub trait VideoSink {
+    /// `pixel` is the 9-bit value, `(emphasis << 6) | colour`, and `rgb` its
+    /// colour in the console's current palette.
+    fn put_pixel(&mut self, x: u32, y: u32, pixel: u16, rgb: Rgb);
+}
+
+/// Packed RGB24.
+impl VideoSink for [u8; FRAME_SIZE_BYTES] {
+    fn put_pixel(&mut self, x: u32, y: u32, _pixel: u16, rgb: Rgb) {
+        let idx = (((y * DISPLAY_WIDTH) + x) * 3) as usize;
+        PixelFormat::Rgb24.encode(rgb, &mut self[idx..idx + 3]);
+    }
+}
+
+/// Raw 9-bit pixel values.
+impl VideoSink for [u16; FRAME_SIZE_PIXELS] {
+    fn put_pixel(&mut self, x: u32, y: u32, pixel: u16, _rgb: Rgb) {
+        self[((y * DISPLAY_WIDTH) + x) as usize] = pixel;
+    }
+}
+
+/// Pixel layouts for `PixelBuffer`. The 8888 formats with a colour in their
+/// name are in byte order, `Rgb565` and `Xrgb8888` are native-endian `u16`
+/// and `u32` values.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PixelFormat {
+    Rgb24,
+    Rgba8888,
+    Bgra8888,
+    Rgb565,
+    Xrgb8888,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgb24 => 3,
+            PixelFormat::Rgb565 => 2,
+            PixelFormat::Rgba8888 | PixelFormat::Bgra8888 | PixelFormat::Xrgb8888 => 4,
+        }
+    }
+
+    /// Writes `rgb` into `out`, which must be `bytes_per_pixel` long.
+    pub fn encode(self, rgb: Rgb, out: &mut [u8]) {
+        let Rgb(r, g, b) = rgb;
+
+        match self {
+            PixelFormat::Rgb24 => out.copy_from_slice(&[r, g, b]),
+            PixelFormat::Rgba8888 => out.copy_from_slice(&[r, g, b, 0xFF]),
+            PixelFormat::Bgra8888 => out.copy_from_slice(&[b, g, r, 0xFF]),
+            PixelFormat::Rgb565 => {
+                let val = (((r as u16) >> 3) << 11) | (((g as u16) >> 2) << 5) | ((b as u16) >> 3);
+                out.copy_from_slice(&val.to_ne_bytes());
+            }
+            PixelFormat::Xrgb8888 => {
+                let val = ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+                out.copy_from_slice(&val.to_ne_bytes());
+            }
+        }
+    }
+}
+
+/// A frame in any `PixelFormat`, with rows packed one after another.
+pub struct PixelBuffer {
+    format: PixelFormat,
+    data: Vec<u8>,
+}
+
+impl PixelBuffer {
+    pub fn init(format: PixelFormat) -> Self {
+        Self {
+            format,
+            data: vec![0; FRAME_SIZE_PIXELS * format.bytes_per_pixel()],
+        }
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Bytes per row.
+    pub fn pitch(&self) -> usize {
+        DISPLAY_WIDTH as usize * self.format.bytes_per_pixel()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl VideoSink for PixelBuffer {
+    fn put_pixel(&mut self, x: u32, y: u32, _pixel: u16, rgb: Rgb) {
+        debug_assert!(x < DISPLAY_WIDTH && y < DISPLAY_HEIGHT);
+
+        let size = self.format.bytes_per_pixel();
+        let idx = (y as usize * self.pitch()) + (x as usize * size);
+        self.format.encode(rgb, &mut self.data[idx..idx + size]);
+    }
+}